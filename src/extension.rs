@@ -1,10 +1,10 @@
 //! Extensions
 //!
-//! TODO: YAML declaration and parsing. This should be similar to a plugin
-//! system (outside the `types` module), which also parses nested [`OpDef`]s.
+//! See [`declarative`] for YAML declaration and parsing, a plugin-like
+//! system (outside the `types` module) which also parses nested [`OpDef`]s.
 
 use std::collections::hash_map::Entry;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::{Debug, Display, Formatter};
 use std::sync::Arc;
 
@@ -16,7 +16,17 @@ use crate::ops;
 use crate::ops::custom::{ExtensionOp, OpaqueOp};
 use crate::types::type_param::{check_type_args, TypeArgError};
 use crate::types::type_param::{TypeArg, TypeParam};
-use crate::types::{check_typevar_decl, CustomType, PolyFuncType, Substitution, TypeBound};
+use crate::types::unify::UnificationError;
+use crate::types::{check_typevar_decl, CustomType, PolyFuncType, Substitution, TypeBound, Variance};
+
+mod const_fold;
+pub use const_fold::{ConstFoldFn, ConstFoldRegistry};
+
+mod declarative;
+pub use declarative::{
+    CustomSignatureFn, DeclarativeLoadError, ExtensionDecl, OpDecl, SignatureFuncRegistry,
+    SignatureSource, TypeDecl,
+};
 
 mod infer;
 pub use infer::{infer_extensions, ExtensionSolution, InferExtensionError};
@@ -25,6 +35,10 @@ mod op_def;
 pub use op_def::{CustomSignatureFunc, OpDef};
 mod type_def;
 pub use type_def::{TypeDef, TypeDefBound};
+mod type_bound;
+pub use type_bound::ExtensionBound;
+mod type_scheme;
+pub use type_scheme::{ArgPathElem, OpDefTypeScheme, ParamConstraint};
 pub mod prelude;
 pub mod validate;
 
@@ -32,12 +46,17 @@ pub use prelude::{PRELUDE, PRELUDE_REGISTRY};
 
 /// Extension Registries store extensions to be looked up e.g. during validation.
 #[derive(Clone, Debug)]
-pub struct ExtensionRegistry(BTreeMap<ExtensionId, Extension>);
+pub struct ExtensionRegistry(
+    BTreeMap<ExtensionId, Extension>,
+    /// Declared implications between [ExtensionBound]s - see
+    /// [ExtensionRegistry::declare_bound_implies].
+    BTreeMap<ExtensionBound, BTreeSet<ExtensionBound>>,
+);
 
 impl ExtensionRegistry {
     /// Makes a new (empty) registry.
     pub const fn new() -> Self {
-        Self(BTreeMap::new())
+        Self(BTreeMap::new(), BTreeMap::new())
     }
 
     /// Gets the Extension with the given name
@@ -97,6 +116,15 @@ pub enum SignatureError {
     /// A type variable that was used has not been declared
     #[error("Type variable {idx} was not declared ({num_decls} in scope)")]
     FreeTypeVar { idx: usize, num_decls: usize },
+    /// While inferring [TypeArg]s for a [PolyFuncType] against a concrete
+    /// target (see [PolyFuncType::infer_instantiate]), the same variable was
+    /// matched against two different concrete values.
+    #[error("Conflicting values inferred for type variable {index}: {first:?} and {second:?}")]
+    InferenceConflict {
+        index: usize,
+        first: TypeArg,
+        second: TypeArg,
+    },
     /// The type stored in a [LeafOp::TypeApply] is not what we compute from the
     /// [ExtensionRegistry]. (Note: might be commoned up with
     /// [CustomOpError::SignatureMismatch] if we implement
@@ -109,6 +137,74 @@ pub enum SignatureError {
         stored: PolyFuncType,
         expected: PolyFuncType,
     },
+    /// A custom type's advertised [ExtensionBound]s do not satisfy one
+    /// required by a polymorphic signature's parameter declaration.
+    #[error("Type's extension bounds {advertised:?} do not satisfy required bound {required}")]
+    ExtensionBoundMismatch {
+        required: ExtensionBound,
+        advertised: BTreeSet<ExtensionBound>,
+    },
+    /// Unifying a type scheme's declared body against concrete operand types
+    /// (to infer its [TypeArg]s) failed.
+    #[error("Could not infer type arguments from input types: {0}")]
+    CannotInferArgs(#[from] UnificationError),
+    /// Every input type was consistent with the scheme, but some parameter
+    /// never occurred in the body and so has no inferred value.
+    #[error("Could not infer type argument at index {index}: it does not occur in any input")]
+    CannotInferParam { index: usize },
+    /// A [TypeArg::Type] argument's bound isn't compatible with its
+    /// parameter's declared bound, given the parameter's inferred [Variance].
+    #[error("Type argument at index {index} has bound {found:?}, which is not compatible with the declared bound {required:?} under {variance:?} variance")]
+    VarianceViolation {
+        index: usize,
+        required: TypeBound,
+        variance: Variance,
+        found: TypeBound,
+    },
+    /// A declared [ParamConstraint] relating two or more of an
+    /// [OpDefTypeScheme]'s parameters was not satisfied by the actual args.
+    #[error("Type arguments {args:?} do not satisfy declared constraint {constraint:?}")]
+    ConstraintUnsatisfied {
+        constraint: ParamConstraint,
+        args: Vec<TypeArg>,
+    },
+    /// A [TypeArg] failed its [TypeParam] check, possibly nested inside
+    /// another argument rather than at the top level - `path` records the
+    /// steps taken to reach it (e.g. argument 0, then index 1 of its
+    /// [CustomType](crate::types::CustomType) args), so the error names the
+    /// exact location of the offending value instead of just the top-level
+    /// pair it was found within.
+    #[error("Type argument mismatch at {path:?}: expected {expected:?}, found {found:?}")]
+    ArgMismatchAt {
+        path: Vec<ArgPathElem>,
+        expected: TypeParam,
+        found: TypeArg,
+    },
+    /// [FunctionType::compose](crate::types::FunctionType::compose) was
+    /// asked to sequence two signatures whose output and input rows don't
+    /// match.
+    #[error("Cannot compose signature with output {output:?} into signature with input {input:?}")]
+    SignatureCompositionMismatch {
+        /// The first signature's output row.
+        output: crate::types::TypeRow,
+        /// The second signature's input row.
+        input: crate::types::TypeRow,
+    },
+    /// [PolyFuncType::match_concrete](crate::types::PolyFuncType::match_concrete)
+    /// found two row variables in the same row with no fixed type between
+    /// them, so there's no way to tell where the first ends and the second
+    /// begins.
+    #[error("Ambiguous match: row variables {first_idx} and {second_idx} are adjacent in the same row")]
+    AmbiguousRowVariables { first_idx: usize, second_idx: usize },
+    /// [PolyFuncType::match_concrete](crate::types::PolyFuncType::match_concrete)
+    /// could not reconcile a pattern row against a concrete row - either a
+    /// fixed entry failed to unify, or there were too few concrete entries
+    /// left to cover every row variable and fixed entry in the pattern.
+    #[error("Could not match pattern row {pattern:?} against concrete row {concrete:?}")]
+    RowMatchMismatch {
+        pattern: crate::types::TypeRow,
+        concrete: crate::types::TypeRow,
+    },
 }
 
 /// Concrete instantiations of types and operations defined in extensions.
@@ -335,47 +431,67 @@ pub enum ExtensionBuildError {
     TypeDefExists(SmolStr),
 }
 
-/// A set of extensions identified by their unique [`ExtensionId`].
-#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
-pub struct ExtensionSet(HashSet<ExtensionId>);
+/// A set of extensions identified by their unique [`ExtensionId`], which may
+/// also contain unresolved type variables (declared as [`TypeParam::Extensions`]).
+///
+/// Concrete extensions and variables are kept in separate fields rather than
+/// smuggling a variable's De Bruijn index into the `ExtensionId` set as a
+/// radix-10 string (as this used to do): that representation depended on no
+/// `ExtensionId` ever starting with a digit, and every operation
+/// below had to re-derive which kind of element it was looking at by
+/// checking for a leading digit. With the two kept apart, `validate`,
+/// `substitute`, `union`, `missing_from`, `contains` and `is_subset` just
+/// operate on whichever field(s) are relevant.
+///
+/// Serializes to the same shape as before (a flat list of extension names,
+/// with a variable's De Bruijn index written out as a decimal string), so
+/// existing serialized `ExtensionSet`s still deserialize correctly.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExtensionSet {
+    concrete: HashSet<ExtensionId>,
+    variables: BTreeSet<usize>,
+}
 
 impl ExtensionSet {
     /// Creates a new empty extension set.
     pub fn new() -> Self {
-        Self(HashSet::new())
+        Self {
+            concrete: HashSet::new(),
+            variables: BTreeSet::new(),
+        }
     }
 
     /// Creates a new extension set from some extensions.
     pub fn new_from_extensions(extensions: impl Into<HashSet<ExtensionId>>) -> Self {
-        Self(extensions.into())
+        Self {
+            concrete: extensions.into(),
+            variables: BTreeSet::new(),
+        }
     }
 
     /// Adds a extension to the set.
     pub fn insert(&mut self, extension: &ExtensionId) {
-        self.0.insert(extension.clone());
+        self.concrete.insert(extension.clone());
     }
 
     /// Adds a type var (which must have been declared as a [TypeParam::Extensions]) to this set
     pub fn insert_type_var(&mut self, idx: usize) {
-        // Represent type vars as string representation of DeBruijn index.
-        // This is not a legal IdentList or ExtensionId so should not conflict.
-        self.0
-            .insert(ExtensionId::new_unchecked(idx.to_string().as_str()));
+        self.variables.insert(idx);
     }
 
     /// Returns `true` if the set contains the given extension.
     pub fn contains(&self, extension: &ExtensionId) -> bool {
-        self.0.contains(extension)
+        self.concrete.contains(extension)
     }
 
     /// Returns `true` if the set is a subset of `other`.
     pub fn is_subset(&self, other: &Self) -> bool {
-        self.0.is_subset(&other.0)
+        self.concrete.is_subset(&other.concrete) && self.variables.is_subset(&other.variables)
     }
 
     /// Returns `true` if the set is a superset of `other`.
     pub fn is_superset(&self, other: &Self) -> bool {
-        self.0.is_superset(&other.0)
+        other.is_subset(self)
     }
 
     /// Create a extension set with a single element.
@@ -395,55 +511,89 @@ impl ExtensionSet {
 
     /// Returns the union of two extension sets.
     pub fn union(mut self, other: &Self) -> Self {
-        self.0.extend(other.0.iter().cloned());
+        self.concrete.extend(other.concrete.iter().cloned());
+        self.variables.extend(other.variables.iter().copied());
         self
     }
 
     /// The things in other which are in not in self
     pub fn missing_from(&self, other: &Self) -> Self {
-        ExtensionSet(HashSet::from_iter(other.0.difference(&self.0).cloned()))
+        Self {
+            concrete: other.concrete.difference(&self.concrete).cloned().collect(),
+            variables: other
+                .variables
+                .difference(&self.variables)
+                .copied()
+                .collect(),
+        }
     }
 
-    /// Iterate over the contained ExtensionIds
+    /// Iterate over the contained (concrete) ExtensionIds
     pub fn iter(&self) -> impl Iterator<Item = &ExtensionId> {
-        self.0.iter()
+        self.concrete.iter()
     }
 
     pub(crate) fn validate(&self, params: &[TypeParam]) -> Result<(), SignatureError> {
-        self.iter()
-            .filter_map(as_typevar)
-            .try_for_each(|var_idx| check_typevar_decl(params, var_idx, &TypeParam::Extensions))
+        self.variables
+            .iter()
+            .try_for_each(|&var_idx| check_typevar_decl(params, var_idx, &TypeParam::Extensions))
     }
 
     pub(crate) fn substitute(&self, sub: &Substitution) -> Self {
-        Self::from_iter(self.0.iter().flat_map(|e| match as_typevar(e) {
-            None => vec![e.clone()],
-            Some(i) => match sub.apply_var(i, &TypeParam::Extensions) {
-                TypeArg::Extensions{es} => es.iter().cloned().collect::<Vec<_>>(),
-                _ => panic!("value for type var was not extension set - type scheme should be validate()d first"),
-            },
-        }))
-    }
-}
-
-fn as_typevar(e: &ExtensionId) -> Option<usize> {
-    // Type variables are represented as radix-10 numbers, which are illegal
-    // as standard ExtensionIds. Hence if an ExtensionId starts with a digit,
-    // we assume it must be a type variable, and fail fast if it isn't.
-    match e.chars().next() {
-        Some(c) if c.is_ascii_digit() => Some(str::parse(e).unwrap()),
-        _ => None,
+        let mut result = Self::new_from_extensions(self.concrete.clone());
+        for &idx in &self.variables {
+            match sub.apply_var(idx, &TypeParam::Extensions) {
+                TypeArg::Extensions { es } => result = result.union(&es),
+                _ => panic!(
+                    "value for type var was not extension set - type scheme should be validate()d first"
+                ),
+            }
+        }
+        result
     }
 }
 
 impl Display for ExtensionSet {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        f.debug_list().entries(self.0.iter()).finish()
+        f.debug_list()
+            .entries(self.concrete.iter().map(ToString::to_string))
+            .entries(self.variables.iter().map(ToString::to_string))
+            .finish()
     }
 }
 
 impl FromIterator<ExtensionId> for ExtensionSet {
     fn from_iter<I: IntoIterator<Item = ExtensionId>>(iter: I) -> Self {
-        Self(HashSet::from_iter(iter))
+        Self::new_from_extensions(HashSet::from_iter(iter))
+    }
+}
+
+impl serde::Serialize for ExtensionSet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            self.concrete
+                .iter()
+                .map(ToString::to_string)
+                .chain(self.variables.iter().map(ToString::to_string)),
+        )
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for ExtensionSet {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut set = Self::new();
+        for entry in Vec::<String>::deserialize(deserializer)? {
+            // Type variables were (and still are, on the wire) written out
+            // as radix-10 numbers, which are illegal as standard
+            // ExtensionIds - so a leading digit means a variable.
+            match entry.chars().next() {
+                Some(c) if c.is_ascii_digit() => {
+                    let idx = entry.parse().map_err(serde::de::Error::custom)?;
+                    set.insert_type_var(idx);
+                }
+                _ => set.insert(&ExtensionId::new_unchecked(&entry)),
+            }
+        }
+        Ok(set)
     }
 }
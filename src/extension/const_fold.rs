@@ -0,0 +1,118 @@
+//! Constant folding driven by an [`OpDef`]'s declared semantics.
+//!
+//! Parallels [`CustomSignatureFunc`]: alongside the function that computes
+//! an op's signature, an extension can register a function that computes its
+//! *result* once every input is already a known constant. A registry-driven
+//! graph pass can then walk a HUGR and, wherever a node's inputs are all
+//! constants and its op has a registered folder, replace the node with its
+//! constant output instead of leaving it to run.
+//!
+//! That's the design; what's actually delivered here is only
+//! [`ConstFoldRegistry`] itself (register/lookup by name), and it stays
+//! unpopulated and unused. Both further pieces the original request asked
+//! for are blocked on code that isn't part of this snapshot:
+//!
+//! - "Add a `ConstFold` capability to `OpDef`" needs `OpDef`'s own
+//!   definition, which lives in `extension/op_def.rs` - `mod op_def;` is
+//!   declared in this crate's `extension` module, but that file isn't
+//!   present here, so there's no struct to add a field to.
+//! - Writing a real folder (for `fadd`, `fmul`, etc.) needs to read a
+//!   float out of an [`ops::Const`](crate::ops::Const) and wrap a result
+//!   back into one, which needs `Const`'s variants - defined in `ops.rs`,
+//!   also not part of this snapshot. `crate::std_extensions::arithmetic::float_ops`'s
+//!   `const_fold_ops` module has the pure `f64` semantics ready for when
+//!   that lands, but nothing here can bridge them to a real `ConstFoldFn`
+//!   today, and a graph-walking fold pass has the same dependency on top.
+//!
+//! The tests below exercise only what doesn't depend on `Const`'s shape:
+//! the registry's own register/overwrite/lookup bookkeeping.
+//!
+//! [`OpDef`]: super::OpDef
+//! [`CustomSignatureFunc`]: super::CustomSignatureFunc
+use std::collections::HashMap;
+
+use crate::ops::Const;
+use crate::types::type_param::TypeArg;
+
+/// Computes an operation's constant outputs from its type args and any
+/// already-known constant inputs (`None` for an input that isn't constant),
+/// or returns `None` if folding doesn't apply - e.g. because an input the
+/// folder needs isn't actually constant yet.
+pub type ConstFoldFn = fn(&[TypeArg], &[Option<Const>]) -> Option<Vec<Const>>;
+
+/// Maps an operation's name to its registered [`ConstFoldFn`], the same role
+/// [`SignatureFuncRegistry`](super::SignatureFuncRegistry) plays for
+/// signature functions.
+#[derive(Default, Clone)]
+pub struct ConstFoldRegistry(HashMap<String, ConstFoldFn>);
+
+impl ConstFoldRegistry {
+    /// Creates a new, empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `f` as the folder for the operation named `name`.
+    pub fn register(&mut self, name: impl Into<String>, f: ConstFoldFn) -> &mut Self {
+        self.0.insert(name.into(), f);
+        self
+    }
+
+    /// The folder registered for `name`, if any.
+    pub fn get(&self, name: &str) -> Option<ConstFoldFn> {
+        self.0.get(name).copied()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A placeholder folder. This crate can't construct or inspect a real
+    /// [`Const`] (see the [module docs](self)), so this never actually
+    /// folds anything - it exists only to give the tests below a
+    /// `ConstFoldFn`-shaped value to register.
+    fn never_folds(_args: &[TypeArg], _inputs: &[Option<Const>]) -> Option<Vec<Const>> {
+        None
+    }
+
+    fn other_never_folds(_args: &[TypeArg], _inputs: &[Option<Const>]) -> Option<Vec<Const>> {
+        None
+    }
+
+    #[test]
+    fn unregistered_name_returns_none() {
+        let registry = ConstFoldRegistry::new();
+        assert!(registry.get("fadd").is_none());
+    }
+
+    #[test]
+    fn get_returns_the_registered_function() {
+        let mut registry = ConstFoldRegistry::new();
+        registry.register("fadd", never_folds);
+        assert_eq!(registry.get("fadd"), Some(never_folds as ConstFoldFn));
+        // A different, never-registered name is still unaffected.
+        assert!(registry.get("fmul").is_none());
+    }
+
+    #[test]
+    fn registering_the_same_name_twice_overwrites() {
+        let mut registry = ConstFoldRegistry::new();
+        registry.register("fadd", never_folds);
+        registry.register("fadd", other_never_folds);
+        assert_eq!(
+            registry.get("fadd"),
+            Some(other_never_folds as ConstFoldFn)
+        );
+    }
+
+    #[test]
+    fn register_returns_self_for_chaining() {
+        let mut registry = ConstFoldRegistry::new();
+        registry
+            .register("fadd", never_folds)
+            .register("fmul", never_folds);
+        assert!(registry.get("fadd").is_some());
+        assert!(registry.get("fmul").is_some());
+    }
+}
@@ -0,0 +1,183 @@
+//! Declarative (YAML/JSON) loading of [`Extension`]s, as referenced from the
+//! module-level docs of [`crate::extension`].
+//!
+//! An [`ExtensionDecl`] is the serializable shape a document is parsed into:
+//! a name, its [`ExtensionSet`] requirements, and its [`TypeDecl`]/[`OpDecl`]
+//! lists. [`ExtensionDecl::load`] then builds a real [`Extension`] from it,
+//! the same way [`crate::std_extensions::arithmetic::float_ops::extension`] builds one by
+//! hand, so that an extension defined today in Rust can instead ship as data
+//! and be loaded without recompiling this crate.
+//!
+//! A [`TypeDecl`] carries everything [`Extension::add_type`] needs directly.
+//! An [`OpDecl`]'s signature can't be embedded in the document the same way,
+//! since [`SignatureError`]-computing closures aren't serializable - instead
+//! it names a [`CustomSignatureFn`] which the caller must have registered in
+//! a [`SignatureFuncRegistry`] before calling [`ExtensionDecl::load`], with
+//! [`DeclarativeLoadError::UnknownSignatureFunc`] raised for any name that
+//! isn't.
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use smol_str::SmolStr;
+use thiserror::Error;
+
+use crate::types::type_param::{TypeArg, TypeParam};
+use crate::types::FunctionType;
+
+use super::{Extension, ExtensionBuildError, ExtensionId, SignatureError, TypeDefBound};
+
+/// A signature-computing function a [`SignatureSource::Named`] can refer to,
+/// matching the signature [`Extension::add_op_custom_sig_simple`] already
+/// takes.
+pub type CustomSignatureFn = fn(&[TypeArg]) -> Result<FunctionType, SignatureError>;
+
+/// Maps the names an [`OpDecl`] can reference to the actual
+/// [`CustomSignatureFn`]s compiled into this binary - the bridge between a
+/// serialized extension and the Rust code that knows how to type-check its
+/// operations.
+#[derive(Default, Clone)]
+pub struct SignatureFuncRegistry(HashMap<String, CustomSignatureFn>);
+
+impl SignatureFuncRegistry {
+    /// Creates a new, empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `f` under `name`, for an [`OpDecl`] to reference via
+    /// [`SignatureSource::Named`].
+    pub fn register(&mut self, name: impl Into<String>, f: CustomSignatureFn) -> &mut Self {
+        self.0.insert(name.into(), f);
+        self
+    }
+}
+
+/// The declarative description of a single [`TypeDef`](super::TypeDef).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TypeDecl {
+    /// The type's extension-unique name.
+    pub name: SmolStr,
+    /// The type's type parameters.
+    #[serde(default)]
+    pub params: Vec<TypeParam>,
+    /// A human-readable description of the type.
+    #[serde(default)]
+    pub description: String,
+    /// How the type's [`TypeBound`](crate::types::TypeBound) is computed.
+    pub bound: TypeDefBound,
+}
+
+/// Where an [`OpDecl`]'s signature-computing function comes from.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SignatureSource {
+    /// Look up this name in the [`SignatureFuncRegistry`] passed to
+    /// [`ExtensionDecl::load`].
+    Named(String),
+}
+
+/// The declarative description of a single [`OpDef`](super::OpDef).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OpDecl {
+    /// The operation's extension-unique name.
+    pub name: SmolStr,
+    /// The operation's type parameters.
+    #[serde(default)]
+    pub params: Vec<TypeParam>,
+    /// A human-readable description of the operation.
+    #[serde(default)]
+    pub description: String,
+    /// Where to find the function that computes this operation's signature
+    /// from its [`TypeArg`]s.
+    pub signature: SignatureSource,
+}
+
+/// The serializable description a YAML/JSON document is parsed into, before
+/// [`ExtensionDecl::load`] turns it into a real [`Extension`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExtensionDecl {
+    /// The extension's unique identifier.
+    pub name: ExtensionId,
+    /// Other extensions defining types this extension's operations can
+    /// return, as in [`Extension::extension_reqs`](super::Extension)'s
+    /// [`ExtensionSet`](super::ExtensionSet).
+    #[serde(default)]
+    pub extension_reqs: Vec<ExtensionId>,
+    /// The types this extension defines.
+    #[serde(default)]
+    pub types: Vec<TypeDecl>,
+    /// The operations this extension defines.
+    #[serde(default)]
+    pub operations: Vec<OpDecl>,
+}
+
+/// An error loading an [`ExtensionDecl`] into a real [`Extension`].
+#[derive(Debug, Clone, Error)]
+pub enum DeclarativeLoadError {
+    /// Parsing the source document itself failed.
+    #[error("failed to parse extension declaration: {0}")]
+    Parse(String),
+    /// An [`OpDecl`] named a [`SignatureSource::Named`] function that wasn't
+    /// registered in the [`SignatureFuncRegistry`] passed to
+    /// [`ExtensionDecl::load`].
+    #[error("operation '{op}' references unregistered signature function '{name}'")]
+    UnknownSignatureFunc {
+        /// The operation that referenced the missing function.
+        op: SmolStr,
+        /// The unregistered name.
+        name: String,
+    },
+    /// Building the [`Extension`] from the parsed declaration failed, e.g.
+    /// because two types or operations share a name.
+    #[error(transparent)]
+    Build(#[from] ExtensionBuildError),
+}
+
+impl ExtensionDecl {
+    /// Parses a YAML document into an [`ExtensionDecl`].
+    ///
+    /// This only parses the declaration's shape; operations' signature
+    /// functions are resolved separately by [`Self::load`], since which
+    /// functions are available depends on what the caller has registered.
+    pub fn from_yaml_str(s: &str) -> Result<Self, DeclarativeLoadError> {
+        serde_yaml::from_str(s).map_err(|e| DeclarativeLoadError::Parse(e.to_string()))
+    }
+
+    /// Builds a real [`Extension`] from this declaration, resolving each
+    /// operation's [`SignatureSource`] against `signature_funcs`.
+    pub fn load(
+        &self,
+        signature_funcs: &SignatureFuncRegistry,
+    ) -> Result<Extension, DeclarativeLoadError> {
+        let mut extension = Extension::new_with_reqs(
+            self.name.clone(),
+            self.extension_reqs.iter().cloned().collect(),
+        );
+
+        for t in &self.types {
+            extension.add_type(
+                t.name.clone(),
+                t.params.clone(),
+                t.description.clone(),
+                t.bound.clone(),
+            )?;
+        }
+
+        for op in &self.operations {
+            let SignatureSource::Named(name) = &op.signature;
+            let sig_fn = *signature_funcs.0.get(name).ok_or_else(|| {
+                DeclarativeLoadError::UnknownSignatureFunc {
+                    op: op.name.clone(),
+                    name: name.clone(),
+                }
+            })?;
+            extension.add_op_custom_sig_simple(
+                op.name.clone(),
+                op.description.clone(),
+                op.params.clone(),
+                sig_fn,
+            )?;
+        }
+
+        Ok(extension)
+    }
+}
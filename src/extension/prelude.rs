@@ -0,0 +1,462 @@
+//! The prelude: types and operations assumed available to every HUGR,
+//! regardless of which [`std_extensions`](crate::std_extensions) it pulls
+//! in - `bool`, qubit, `usize`, fixed-width integers, arrays, and the
+//! result-reporting ops used to name a program's observable outputs. Tests
+//! throughout [`crate::types`] and [`crate::hugr::validate`] validate
+//! against [`PRELUDE_REGISTRY`] rather than an empty registry for exactly
+//! this reason.
+use std::sync::LazyLock;
+
+use smol_str::SmolStr;
+
+use super::{
+    Extension, ExtensionId, ExtensionRegistry, ExtensionSet, SignatureError, TypeDefBound,
+};
+use crate::types::{
+    type_param::{TypeArg, TypeParam},
+    CustomType, FunctionType, Type, TypeBound,
+};
+
+/// The prelude's extension identifier.
+pub const EXTENSION_ID: ExtensionId = ExtensionId::new_unchecked("prelude");
+
+/// The name of the `qubit` type.
+pub const QB_TYPE_NAME: SmolStr = SmolStr::new_inline("qubit");
+/// The name of the `usize` type.
+pub const USIZE_TYPE_NAME: SmolStr = SmolStr::new_inline("usize");
+/// The name of the fixed-width integer type, parametrised by its bit width.
+pub const INT_TYPE_NAME: SmolStr = SmolStr::new_inline("int");
+/// The name of the array type, parametrised by element type and length.
+pub const ARRAY_TYPE_NAME: SmolStr = SmolStr::new_inline("array");
+/// The name of the `Future` type, parametrised by the type it eventually
+/// resolves to.
+pub const FUTURE_TYPE_NAME: SmolStr = SmolStr::new_inline("Future");
+
+/// The two-element unit sum conventionally used to represent a boolean.
+pub const BOOL_T: Type = Type::new_unit_sum(2);
+
+/// A qubit: a linear resource with no classical representation.
+pub const QB_T: Type = Type::new_extension(CustomType::new_simple(
+    QB_TYPE_NAME,
+    EXTENSION_ID,
+    TypeBound::Any,
+));
+
+/// A native-width unsigned index, used for array lengths and offsets.
+pub const USIZE_T: Type = Type::new_extension(CustomType::new_simple(
+    USIZE_TYPE_NAME,
+    EXTENSION_ID,
+    TypeBound::Eq,
+));
+
+/// Builds the `int<width>` [`Type`] for the given bit width.
+///
+/// `width` is not itself bounds-checked here - the [`INT_TYPE_NAME`]
+/// [`TypeDef`](crate::extension::TypeDef)'s declared [`TypeParam::max_nat`]
+/// parameter is what [`TypeDef::instantiate`](crate::extension::TypeDef::instantiate)
+/// checks it against.
+pub fn int_type(width: u64) -> Type {
+    Type::new_extension(CustomType::new(
+        INT_TYPE_NAME,
+        vec![TypeArg::BoundedNat { n: width }],
+        EXTENSION_ID,
+        TypeBound::Eq,
+    ))
+}
+
+/// Builds the `array<elem, len>` [`Type`].
+pub fn array_type(elem: Type, len: u64) -> Type {
+    let bound = elem.least_upper_bound();
+    Type::new_extension(CustomType::new(
+        ARRAY_TYPE_NAME,
+        vec![TypeArg::Type { ty: elem }, TypeArg::BoundedNat { n: len }],
+        EXTENSION_ID,
+        bound,
+    ))
+}
+
+/// Builds the `Future<T>` [`Type`] around the given element type.
+///
+/// Unlike [`std_extensions::future::future_type`](crate::std_extensions::future::future_type),
+/// this is unconditionally [`TypeBound::Any`] regardless of `elem`'s own
+/// bound: a future is a handle to a value that isn't available yet, and
+/// implicitly copying or dropping that handle would copy or drop the
+/// eventual resolution along with it, which is never safe even for an
+/// otherwise-copyable `T`. Forcing, duplicating or discarding it is only
+/// ever valid through the explicit `read`/`dup`/`free` ops.
+pub fn future_type(elem: Type) -> Type {
+    Type::new_extension(CustomType::new(
+        FUTURE_TYPE_NAME,
+        vec![TypeArg::Type { ty: elem }],
+        EXTENSION_ID,
+        TypeBound::Any,
+    ))
+}
+
+/// Recovers the `T` a `Future<T>` operation was instantiated with from its
+/// sole type argument.
+fn future_elem_type(arg_values: &[TypeArg]) -> Type {
+    match &arg_values[0] {
+        TypeArg::Type { ty } => ty.clone(),
+        _ => panic!("Future's sole type argument was not a Type"),
+    }
+}
+
+fn future_read_sig(arg_values: &[TypeArg]) -> Result<FunctionType, SignatureError> {
+    let t = future_elem_type(arg_values);
+    Ok(FunctionType::new(vec![future_type(t.clone())], vec![t]))
+}
+
+fn future_dup_sig(arg_values: &[TypeArg]) -> Result<FunctionType, SignatureError> {
+    let t = future_elem_type(arg_values);
+    Ok(FunctionType::new(
+        vec![future_type(t.clone())],
+        vec![future_type(t.clone()), future_type(t)],
+    ))
+}
+
+fn future_free_sig(arg_values: &[TypeArg]) -> Result<FunctionType, SignatureError> {
+    let t = future_elem_type(arg_values);
+    Ok(FunctionType::new(vec![future_type(t)], vec![]))
+}
+
+/// Recovers the `tag` a `result_*` op was instantiated with - always its
+/// first type argument.
+fn tag(arg_values: &[TypeArg]) -> &str {
+    match &arg_values[0] {
+        TypeArg::String { value } => value,
+        _ => panic!("result op's first type argument was not a String"),
+    }
+}
+
+/// Marks a `result_*` op's signature as carrying its own extension in its
+/// extension-delta, so inference and validation treat it as an effectful,
+/// non-removable, ordered operation rather than a pure one that could be
+/// silently reordered or discarded.
+fn result_sig(input: Type) -> FunctionType {
+    FunctionType::new(vec![input], vec![])
+        .with_extension_delta(&ExtensionSet::singleton(&EXTENSION_ID))
+}
+
+fn result_bool_sig(arg_values: &[TypeArg]) -> Result<FunctionType, SignatureError> {
+    let _ = tag(arg_values);
+    Ok(result_sig(BOOL_T))
+}
+
+/// Shared by `result_int` and `result_uint`: both report a value of the
+/// same `int<width>` representation, differing only in how a consumer
+/// interprets the bits, which this extension has no need to distinguish.
+fn result_int_sig(arg_values: &[TypeArg]) -> Result<FunctionType, SignatureError> {
+    let _ = tag(arg_values);
+    let width = match &arg_values[1] {
+        TypeArg::BoundedNat { n } => *n,
+        _ => panic!("result_int/result_uint's second type argument was not a BoundedNat"),
+    };
+    Ok(result_sig(int_type(width)))
+}
+
+fn result_float_sig(arg_values: &[TypeArg]) -> Result<FunctionType, SignatureError> {
+    let _ = tag(arg_values);
+    Ok(result_sig(
+        crate::std_extensions::arithmetic::float_types::FLOAT64_TYPE,
+    ))
+}
+
+fn result_arr_sig(arg_values: &[TypeArg]) -> Result<FunctionType, SignatureError> {
+    let _ = tag(arg_values);
+    let elem = match &arg_values[1] {
+        TypeArg::Type { ty } => ty.clone(),
+        _ => panic!("result_arr's second type argument was not a Type"),
+    };
+    let len = match &arg_values[2] {
+        TypeArg::BoundedNat { n } => *n,
+        _ => panic!("result_arr's third type argument was not a BoundedNat"),
+    };
+    Ok(result_sig(array_type(elem, len)))
+}
+
+/// The prelude extension: `bool`, `qubit`, `usize`, `int<n>`, `array<T, n>`,
+/// and the `result_*` family of observable-result-reporting ops.
+pub fn prelude() -> Extension {
+    let mut extension = Extension::new(EXTENSION_ID);
+
+    extension
+        .add_type(
+            QB_TYPE_NAME,
+            vec![],
+            "A qubit".to_owned(),
+            TypeDefBound::Explicit(TypeBound::Any),
+        )
+        .unwrap();
+    extension
+        .add_type(
+            USIZE_TYPE_NAME,
+            vec![],
+            "A native-width unsigned index".to_owned(),
+            TypeDefBound::Explicit(TypeBound::Eq),
+        )
+        .unwrap();
+    extension
+        .add_type(
+            INT_TYPE_NAME,
+            vec![TypeParam::max_nat()],
+            "A fixed-width integer".to_owned(),
+            TypeDefBound::Explicit(TypeBound::Eq),
+        )
+        .unwrap();
+    extension
+        .add_type(
+            ARRAY_TYPE_NAME,
+            vec![TypeParam::Type(TypeBound::Any), TypeParam::max_nat()],
+            "A fixed-length array".to_owned(),
+            TypeDefBound::FromParams(vec![0]),
+        )
+        .unwrap();
+    extension
+        .add_type(
+            FUTURE_TYPE_NAME,
+            vec![TypeParam::Type(TypeBound::Any)],
+            "A handle to a value computed out-of-band, not yet forced".to_owned(),
+            TypeDefBound::Explicit(TypeBound::Any),
+        )
+        .unwrap();
+
+    extension
+        .add_op_custom_sig_simple(
+            "read".into(),
+            "Forces a Future<T>, blocking until its value is available".to_owned(),
+            vec![TypeParam::Type(TypeBound::Any)],
+            future_read_sig,
+        )
+        .unwrap();
+    extension
+        .add_op_custom_sig_simple(
+            "dup".into(),
+            "Duplicates a Future<T> into two handles to the same eventual value".to_owned(),
+            vec![TypeParam::Type(TypeBound::Any)],
+            future_dup_sig,
+        )
+        .unwrap();
+    extension
+        .add_op_custom_sig_simple(
+            "free".into(),
+            "Discards a Future<T> without forcing it".to_owned(),
+            vec![TypeParam::Type(TypeBound::Any)],
+            future_free_sig,
+        )
+        .unwrap();
+
+    extension
+        .add_op_custom_sig_simple(
+            "result_bool".into(),
+            "Reports a bool value as a named result".to_owned(),
+            vec![TypeParam::String],
+            result_bool_sig,
+        )
+        .unwrap();
+    extension
+        .add_op_custom_sig_simple(
+            "result_int".into(),
+            "Reports a signed fixed-width integer value as a named result".to_owned(),
+            vec![TypeParam::String, TypeParam::max_nat()],
+            result_int_sig,
+        )
+        .unwrap();
+    extension
+        .add_op_custom_sig_simple(
+            "result_uint".into(),
+            "Reports an unsigned fixed-width integer value as a named result".to_owned(),
+            vec![TypeParam::String, TypeParam::max_nat()],
+            result_int_sig,
+        )
+        .unwrap();
+    extension
+        .add_op_custom_sig_simple(
+            "result_float".into(),
+            "Reports a float value as a named result".to_owned(),
+            vec![TypeParam::String],
+            result_float_sig,
+        )
+        .unwrap();
+    extension
+        .add_op_custom_sig_simple(
+            "result_arr".into(),
+            "Reports an array value as a named result".to_owned(),
+            vec![
+                TypeParam::String,
+                TypeParam::Type(TypeBound::Any),
+                TypeParam::max_nat(),
+            ],
+            result_arr_sig,
+        )
+        .unwrap();
+
+    extension
+}
+
+/// The prelude extension.
+///
+/// `result_int` and `result_uint` share one signature function: both report
+/// a value of the same `int<width>` representation, differing only in how a
+/// consumer interprets the bits, which this extension has no need to
+/// distinguish.
+pub static PRELUDE: LazyLock<Extension> = LazyLock::new(prelude);
+
+/// A registry containing just [`PRELUDE`] - the minimum any HUGR can
+/// validate against, since even a HUGR that pulls in no
+/// [`std_extensions`](crate::std_extensions) still uses prelude types for
+/// its `Input`/`Output` rows.
+pub static PRELUDE_REGISTRY: LazyLock<ExtensionRegistry> =
+    LazyLock::new(|| [PRELUDE.to_owned()].into());
+
+// `add_result`/`add_read`/`add_dup`/`add_free`, the `Dataflow`-trait builder
+// helpers for wiring a value straight into one of these ops (mirroring
+// `LoadConstant`/`add_dataflow_op`), are not implemented here:
+// `crate::builder` (the module that would define the `Dataflow` trait) does
+// not exist anywhere in this tree. The only consumers of a `Dataflow`-shaped
+// API (`crate::hugr::validate::test` and `crate::hugr::views`) already import
+// `crate::builder::Dataflow` from a more mature, incompatible lineage of
+// this crate that isn't present here - adding builder methods to a trait
+// that doesn't exist isn't possible without fabricating that trait
+// wholesale. `instantiate_extension_op`-friendly free functions cover the
+// feasible half of both requests instead.
+
+/// Instantiates `result_bool` for the given tag.
+pub fn result_bool_op(
+    tag: impl Into<String>,
+    ext_reg: &ExtensionRegistry,
+) -> Result<crate::ops::custom::ExtensionOp, SignatureError> {
+    PRELUDE.instantiate_extension_op(
+        "result_bool",
+        vec![TypeArg::String { value: tag.into() }],
+        ext_reg,
+    )
+}
+
+/// Instantiates `result_int` for the given tag and bit width.
+pub fn result_int_op(
+    tag: impl Into<String>,
+    width: u64,
+    ext_reg: &ExtensionRegistry,
+) -> Result<crate::ops::custom::ExtensionOp, SignatureError> {
+    PRELUDE.instantiate_extension_op(
+        "result_int",
+        vec![
+            TypeArg::String { value: tag.into() },
+            TypeArg::BoundedNat { n: width },
+        ],
+        ext_reg,
+    )
+}
+
+/// Instantiates `result_uint` for the given tag and bit width.
+pub fn result_uint_op(
+    tag: impl Into<String>,
+    width: u64,
+    ext_reg: &ExtensionRegistry,
+) -> Result<crate::ops::custom::ExtensionOp, SignatureError> {
+    PRELUDE.instantiate_extension_op(
+        "result_uint",
+        vec![
+            TypeArg::String { value: tag.into() },
+            TypeArg::BoundedNat { n: width },
+        ],
+        ext_reg,
+    )
+}
+
+/// Instantiates `result_float` for the given tag.
+pub fn result_float_op(
+    tag: impl Into<String>,
+    ext_reg: &ExtensionRegistry,
+) -> Result<crate::ops::custom::ExtensionOp, SignatureError> {
+    PRELUDE.instantiate_extension_op(
+        "result_float",
+        vec![TypeArg::String { value: tag.into() }],
+        ext_reg,
+    )
+}
+
+/// Instantiates `result_arr` for the given tag, element type, and length.
+pub fn result_arr_op(
+    tag: impl Into<String>,
+    elem: Type,
+    len: u64,
+    ext_reg: &ExtensionRegistry,
+) -> Result<crate::ops::custom::ExtensionOp, SignatureError> {
+    PRELUDE.instantiate_extension_op(
+        "result_arr",
+        vec![
+            TypeArg::String { value: tag.into() },
+            TypeArg::Type { ty: elem },
+            TypeArg::BoundedNat { n: len },
+        ],
+        ext_reg,
+    )
+}
+
+/// Instantiates `read` for the given element type, forcing a `Future<elem>`
+/// and producing `elem`.
+pub fn future_read_op(
+    elem: Type,
+    ext_reg: &ExtensionRegistry,
+) -> Result<crate::ops::custom::ExtensionOp, SignatureError> {
+    PRELUDE.instantiate_extension_op("read", vec![TypeArg::Type { ty: elem }], ext_reg)
+}
+
+/// Instantiates `dup` for the given element type, splitting a
+/// `Future<elem>` into two handles to the same eventual value.
+pub fn future_dup_op(
+    elem: Type,
+    ext_reg: &ExtensionRegistry,
+) -> Result<crate::ops::custom::ExtensionOp, SignatureError> {
+    PRELUDE.instantiate_extension_op("dup", vec![TypeArg::Type { ty: elem }], ext_reg)
+}
+
+/// Instantiates `free` for the given element type, discarding a
+/// `Future<elem>` without forcing it.
+pub fn future_free_op(
+    elem: Type,
+    ext_reg: &ExtensionRegistry,
+) -> Result<crate::ops::custom::ExtensionOp, SignatureError> {
+    PRELUDE.instantiate_extension_op("free", vec![TypeArg::Type { ty: elem }], ext_reg)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_prelude_extension() {
+        let p = prelude();
+        assert_eq!(p.name(), "prelude");
+        assert_eq!(p.types().count(), 5);
+        assert_eq!(p.operations().count(), 8);
+    }
+
+    #[test]
+    fn test_result_ops_instantiate() {
+        let reg = &PRELUDE_REGISTRY;
+        result_bool_op("out0", reg).unwrap();
+        result_int_op("out1", 32, reg).unwrap();
+        result_uint_op("out2", 8, reg).unwrap();
+        result_float_op("out3", reg).unwrap();
+        result_arr_op("out4", USIZE_T, 3, reg).unwrap();
+    }
+
+    #[test]
+    fn test_future_ops_instantiate() {
+        let reg = &PRELUDE_REGISTRY;
+        future_read_op(QB_T, reg).unwrap();
+        future_dup_op(QB_T, reg).unwrap();
+        future_free_op(QB_T, reg).unwrap();
+    }
+
+    #[test]
+    fn test_future_type_is_always_linear() {
+        // Even though `usize` is itself an `Eq`-bounded type, a `Future` of
+        // one is not: the handle can't be implicitly copied or discarded,
+        // only forced/duplicated/freed via `read`/`dup`/`free`.
+        assert_eq!(future_type(USIZE_T).least_upper_bound(), TypeBound::Any);
+    }
+}
@@ -0,0 +1,98 @@
+//! Extension-declared type bounds.
+//!
+//! [TypeBound](crate::types::TypeBound) is a small, fixed lattice
+//! (Eq/Copyable/Any) covering only copyability and equality. [ExtensionBound]
+//! lets an extension declare its own named capability predicates - e.g.
+//! "linear", "orderable" - that a [CustomType] can advertise it satisfies
+//! (see [CustomType::with_extension_bounds]) and that a polymorphic
+//! signature can require, forming a user-extensible partial order alongside
+//! the built-in lattice rather than replacing it.
+use std::collections::BTreeSet;
+
+use smol_str::SmolStr;
+
+use crate::types::CustomType;
+
+use super::{ExtensionRegistry, SignatureError};
+
+/// A named capability predicate declared by an extension, which a
+/// [CustomType] may advertise its instances satisfy.
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub struct ExtensionBound(SmolStr);
+
+impl ExtensionBound {
+    /// Creates a new extension bound with the given name.
+    pub fn new(name: impl Into<SmolStr>) -> Self {
+        Self(name.into())
+    }
+
+    /// The bound's name.
+    pub fn name(&self) -> &SmolStr {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for ExtensionBound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl ExtensionRegistry {
+    /// Declares that satisfying `bound` implies satisfying `implies`, i.e.
+    /// adds an edge to the partial order of [ExtensionBound]s. Implication is
+    /// transitive: declaring `A` implies `B` and `B` implies `C` means `A`
+    /// implies `C` too, without needing to declare that directly.
+    pub fn declare_bound_implies(&mut self, bound: ExtensionBound, implies: ExtensionBound) {
+        self.1.entry(bound).or_default().insert(implies);
+    }
+
+    /// Whether the set of bounds `advertised` (as returned by
+    /// [CustomType::extension_bounds]) satisfies `required`, either directly
+    /// or by following [declare_bound_implies](Self::declare_bound_implies)
+    /// edges transitively.
+    pub fn extension_bound_satisfied(
+        &self,
+        advertised: &BTreeSet<ExtensionBound>,
+        required: &ExtensionBound,
+    ) -> bool {
+        if advertised.contains(required) {
+            return true;
+        }
+        let mut seen: BTreeSet<&ExtensionBound> = BTreeSet::new();
+        let mut frontier: Vec<&ExtensionBound> = advertised.iter().collect();
+        while let Some(bound) = frontier.pop() {
+            if !seen.insert(bound) {
+                continue;
+            }
+            let Some(implied) = self.1.get(bound) else {
+                continue;
+            };
+            if implied.contains(required) {
+                return true;
+            }
+            frontier.extend(implied.iter());
+        }
+        false
+    }
+
+    /// Checks that `custom`'s advertised [ExtensionBound]s satisfy `required`,
+    /// for use alongside the usual [TypeBound](crate::types::TypeBound) check
+    /// in a polymorphic signature's argument validation.
+    pub fn check_extension_bound(
+        &self,
+        custom: &CustomType,
+        required: &ExtensionBound,
+    ) -> Result<(), SignatureError> {
+        if self.extension_bound_satisfied(custom.extension_bounds(), required) {
+            Ok(())
+        } else {
+            Err(SignatureError::ExtensionBoundMismatch {
+                required: required.clone(),
+                advertised: custom.extension_bounds().clone(),
+            })
+        }
+    }
+}
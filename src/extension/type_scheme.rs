@@ -2,10 +2,149 @@
 //! The type scheme declares a number of TypeParams; any TypeArgs fitting those,
 //! produce a FunctionType for the Op by substitution.
 
+use smol_str::SmolStr;
+
+use crate::types::poly_func::variances_of;
 use crate::types::type_param::{check_type_args, TypeArg, TypeParam};
-use crate::types::FunctionType;
+use crate::types::unify::unify_type;
+use crate::types::{FunctionType, Type, TypeEnum, Variance};
+
+use super::{
+    CustomSignatureFunc, ExtensionId, ExtensionRegistry, SignatureError, TypeParametrised,
+};
 
-use super::{CustomSignatureFunc, ExtensionRegistry, SignatureError};
+/// A step in the path from an [OpDefTypeScheme]'s top-level arguments down to
+/// a nested [TypeArg] that failed its [TypeParam] check, used by
+/// [SignatureError::ArgMismatchAt] to name the exact location of a mismatch
+/// instead of just the top-level pair it was found within.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArgPathElem {
+    /// The `usize`-th of the scheme's declared arguments (by position).
+    Input(usize),
+    /// The `usize`-th of the scheme's produced outputs, for mismatches
+    /// discovered on the output side of a [FunctionType].
+    Output(usize),
+    /// Type argument `index` of an instance of the named extension type.
+    CustomTypeArg {
+        def: (ExtensionId, SmolStr),
+        index: usize,
+    },
+    /// Element `index` of a [TypeParam::List] argument.
+    ListElem(usize),
+}
+
+/// A predicate relating two or more of an [OpDefTypeScheme]'s declared type
+/// parameters (given by index), checked against the actual [TypeArg]s once
+/// [check_type_args] has confirmed each fits its own declaration
+/// individually. Lets an op require e.g. "these two nat parameters are
+/// equal" or "this type parameter is an instance of this extension type"
+/// without needing a single [TypeParam] expressive enough to say so alone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParamConstraint {
+    /// The [Type]s bound to these two [TypeParam::Type] parameters must have
+    /// the same [TypeBound](crate::types::TypeBound).
+    SameBound(usize, usize),
+    /// The [TypeArg::BoundedNat]s bound to these two parameters must be
+    /// numerically equal.
+    NatEq(usize, usize),
+    /// The [TypeArg::BoundedNat] bound to the first parameter must be `<=`
+    /// the one bound to the second.
+    NatLe(usize, usize),
+    /// The [Type] bound to `ty_param` must be an instance of the named
+    /// extension type `def`, instantiated with exactly the args (in order)
+    /// bound to `arg_params`.
+    TypeIsInstanceOf {
+        ty_param: usize,
+        def: (ExtensionId, SmolStr),
+        arg_params: Vec<usize>,
+    },
+}
+
+/// Evaluates a single [ParamConstraint] against a concrete list of `args`,
+/// indexed the same way as [ParamConstraint::indices]. Shared by
+/// [OpDefTypeScheme::check_constraints], which checks every constraint
+/// against the scheme's actual call-site args, and [OpDefTypeScheme::partial],
+/// which must check the subset of constraints fully satisfied by `prefix`
+/// before dropping them (since the returned scheme no longer carries them to
+/// check later).
+fn constraint_satisfied(constraint: &ParamConstraint, args: &[TypeArg]) -> bool {
+    match constraint {
+        ParamConstraint::SameBound(i, j) => match (&args[*i], &args[*j]) {
+            (TypeArg::Type { ty: a }, TypeArg::Type { ty: b }) => {
+                a.least_upper_bound() == b.least_upper_bound()
+            }
+            _ => false,
+        },
+        ParamConstraint::NatEq(i, j) => {
+            matches!((&args[*i], &args[*j]), (TypeArg::BoundedNat { n: a }, TypeArg::BoundedNat { n: b }) if a == b)
+        }
+        ParamConstraint::NatLe(i, j) => {
+            matches!((&args[*i], &args[*j]), (TypeArg::BoundedNat { n: a }, TypeArg::BoundedNat { n: b }) if a <= b)
+        }
+        ParamConstraint::TypeIsInstanceOf {
+            ty_param,
+            def,
+            arg_params,
+        } => match &args[*ty_param] {
+            TypeArg::Type { ty } => match ty.as_type_enum() {
+                TypeEnum::Extension(c) => {
+                    c.extension() == &def.0
+                        && c.name() == &def.1
+                        && c.args().len() == arg_params.len()
+                        && arg_params
+                            .iter()
+                            .zip(c.args())
+                            .all(|(&idx, actual)| args.get(idx) == Some(actual))
+                }
+                _ => false,
+            },
+            _ => false,
+        },
+    }
+}
+
+impl ParamConstraint {
+    /// The parameter indices this constraint mentions, for range-checking
+    /// against the declared parameter list.
+    fn indices(&self) -> Vec<usize> {
+        match self {
+            ParamConstraint::SameBound(i, j)
+            | ParamConstraint::NatEq(i, j)
+            | ParamConstraint::NatLe(i, j) => vec![*i, *j],
+            ParamConstraint::TypeIsInstanceOf {
+                ty_param,
+                arg_params,
+                ..
+            } => {
+                let mut v = vec![*ty_param];
+                v.extend(arg_params.iter().copied());
+                v
+            }
+        }
+    }
+
+    /// Shifts every parameter index this constraint mentions down by
+    /// `offset`, for use when the leading `offset` parameters have been
+    /// consumed by [OpDefTypeScheme::partial]. Panics if any index is `<
+    /// offset`; callers must only call this on constraints already known
+    /// (via [Self::indices]) to mention no such index.
+    fn reindexed(&self, offset: usize) -> Self {
+        match self {
+            ParamConstraint::SameBound(i, j) => ParamConstraint::SameBound(i - offset, j - offset),
+            ParamConstraint::NatEq(i, j) => ParamConstraint::NatEq(i - offset, j - offset),
+            ParamConstraint::NatLe(i, j) => ParamConstraint::NatLe(i - offset, j - offset),
+            ParamConstraint::TypeIsInstanceOf {
+                ty_param,
+                def,
+                arg_params,
+            } => ParamConstraint::TypeIsInstanceOf {
+                ty_param: ty_param - offset,
+                def: def.clone(),
+                arg_params: arg_params.iter().map(|i| i - offset).collect(),
+            },
+        }
+    }
+}
 
 /// A polymorphic type scheme for an op
 pub struct OpDefTypeScheme<'a> {
@@ -13,6 +152,13 @@ pub struct OpDefTypeScheme<'a> {
     pub params: Vec<TypeParam>,
     /// Template for the Op type. May contain variables up to length of [OpDefTypeScheme::params]
     body: FunctionType,
+    /// The [Variance] of each of [Self::params], inferred once at construction
+    /// time from how its variable occurs in `body` - see [Self::variances].
+    variances: Vec<Variance>,
+    /// Declared predicates relating two or more of [Self::params], checked
+    /// against the actual args in [Self::compute_signature] after
+    /// [check_type_args] has passed.
+    constraints: Vec<ParamConstraint>,
     /// Extensions - the [TypeDefBound]s in here will be needed when we instantiate the [OpDefTypeScheme]
     /// into a [FunctionType].
     ///
@@ -34,15 +180,236 @@ impl<'a> OpDefTypeScheme<'a> {
         params: impl Into<Vec<TypeParam>>,
         body: FunctionType,
         extension_registry: &'a ExtensionRegistry,
+    ) -> Result<Self, SignatureError> {
+        Self::new_with_constraints(params, body, vec![], extension_registry)
+    }
+
+    /// As [Self::new], but additionally declares [ParamConstraint]s relating
+    /// two or more of `params`. Each constraint's parameter indices are
+    /// checked to be in range for `params`; whether the constraint is
+    /// actually *satisfiable* given the params' declared kinds is left to
+    /// [Self::compute_signature] to discover per-call, since that depends on
+    /// the concrete args, not just the declarations.
+    ///
+    /// #Errors
+    /// As [Self::new], plus [SignatureError::InvalidTypeArgs] if any
+    /// constraint references a parameter index `>= params.len()`.
+    pub fn new_with_constraints(
+        params: impl Into<Vec<TypeParam>>,
+        body: FunctionType,
+        constraints: Vec<ParamConstraint>,
+        extension_registry: &'a ExtensionRegistry,
     ) -> Result<Self, SignatureError> {
         let params = params.into();
         body.validate(extension_registry, &params)?;
+        if constraints
+            .iter()
+            .flat_map(ParamConstraint::indices)
+            .any(|i| i >= params.len())
+        {
+            return Err(SignatureError::InvalidTypeArgs);
+        }
+        let variances = variances_of(params.len(), &body);
         Ok(Self {
             params,
             body,
+            variances,
+            constraints,
             exts: extension_registry,
         })
     }
+
+    /// The inferred [Variance] of each of [Self::params] (see
+    /// [crate::types::PolyFuncType::variances] for how it's computed), used
+    /// by [Self::compute_signature] to decide which bound-widening or
+    /// -narrowing substitutions are sound.
+    pub fn variances(&self) -> &[Variance] {
+        &self.variances
+    }
+
+    /// Checks that `args` is consistent with [Self::variances]: a covariant
+    /// parameter's argument bound must be at or below the declared bound, a
+    /// contravariant one at or above it, and an invariant one must match
+    /// exactly. An unused (bivariant) parameter is checked as covariant.
+    fn check_variance(&self, args: &[TypeArg]) -> Result<(), SignatureError> {
+        for (index, (param, arg)) in self.params.iter().zip(args.iter()).enumerate() {
+            let (TypeParam::Type(decl_bound), TypeArg::Type { ty }) = (param, arg) else {
+                continue;
+            };
+            let arg_bound = ty.least_upper_bound();
+            let variance = self
+                .variances
+                .get(index)
+                .copied()
+                .unwrap_or(Variance::Invariant);
+            let sound = match variance {
+                Variance::Covariant | Variance::Bivariant => decl_bound.contains(arg_bound),
+                Variance::Contravariant => arg_bound.contains(*decl_bound),
+                Variance::Invariant => arg_bound == *decl_bound,
+            };
+            if !sound {
+                return Err(SignatureError::VarianceViolation {
+                    index,
+                    required: *decl_bound,
+                    variance,
+                    found: arg_bound,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// As [check_type_args], but on failure reports the exact [ArgPathElem]
+    /// path to the offending nested [TypeArg] rather than just the top-level
+    /// argument it was found within - e.g. `args[0]`'s `Array` instance's
+    /// size argument, not just `args[0]` itself.
+    fn check_args_located(&self, args: &[TypeArg]) -> Result<(), SignatureError> {
+        for (index, (param, arg)) in self.params.iter().zip(args.iter()).enumerate() {
+            let mut path = vec![ArgPathElem::Input(index)];
+            check_type_arg_located(arg, param, &mut path, self.exts)?;
+        }
+        Ok(())
+    }
+
+    /// Evaluates each of [Self::constraints] against the actual `args`.
+    fn check_constraints(&self, args: &[TypeArg]) -> Result<(), SignatureError> {
+        for constraint in &self.constraints {
+            if !constraint_satisfied(constraint, args) {
+                return Err(SignatureError::ConstraintUnsatisfied {
+                    constraint: constraint.clone(),
+                    args: args.to_vec(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Infers this scheme's [TypeArg]s from the concrete types of its
+    /// operands, by unifying each declared input [Type] (which may mention
+    /// this scheme's [TypeParam]-declared variables) against the
+    /// corresponding entry of `inputs` in lockstep, then substituting the
+    /// inferred args to produce the concrete [FunctionType]. Errors with
+    /// [SignatureError::CannotInferArgs] if any input doesn't fit the
+    /// declared shape, or [SignatureError::CannotInferParam] if a declared
+    /// parameter never occurs in the body and so can't be inferred this way.
+    pub fn compute_signature_from_inputs(
+        &self,
+        inputs: &[Type],
+    ) -> Result<(Vec<TypeArg>, FunctionType), SignatureError> {
+        if inputs.len() != self.body.input().len() {
+            return Err(SignatureError::InvalidTypeArgs);
+        }
+        let mut bindings: Vec<Option<TypeArg>> = vec![None; self.params.len()];
+        for (pattern, concrete) in self.body.input().iter().zip(inputs.iter()) {
+            let partial = unify_type(pattern, concrete)?;
+            for (idx, arg) in partial.into_iter().enumerate() {
+                let Some(arg) = arg else { continue };
+                if bindings.len() <= idx {
+                    bindings.resize(idx + 1, None);
+                }
+                match &bindings[idx] {
+                    None => bindings[idx] = Some(arg),
+                    Some(prev) if *prev == arg => {}
+                    Some(_) => return Err(SignatureError::InvalidTypeArgs),
+                }
+            }
+        }
+        let args: Vec<TypeArg> = bindings
+            .into_iter()
+            .enumerate()
+            .map(|(index, b)| b.ok_or(SignatureError::CannotInferParam { index }))
+            .collect::<Result<_, _>>()?;
+        self.check_args_located(&args)?;
+        let sig = self.body.substitute(self.exts, &args);
+        Ok((args, sig))
+    }
+
+    /// Specializes the first `prefix.len()` of [Self::params], returning a
+    /// fresh scheme over the remaining ones. Mirrors applying a substitution
+    /// incrementally rather than all at once: instantiating the result with
+    /// the remaining args gives the same [FunctionType] as instantiating
+    /// `self` with `prefix` followed by those same args.
+    ///
+    /// #Errors
+    /// [SignatureError::TypeArgMismatch] if `prefix` doesn't fit the leading
+    /// [Self::params].
+    pub fn partial(&self, prefix: &[TypeArg]) -> Result<OpDefTypeScheme<'a>, SignatureError> {
+        let num_remaining = self.params.len() - prefix.len();
+        check_type_args(prefix, &self.params[..prefix.len()])
+            .map_err(SignatureError::TypeArgMismatch)?;
+        let remaining_params = &self.params[prefix.len()..];
+        let mut args: Vec<TypeArg> = prefix.to_vec();
+        args.extend(
+            remaining_params
+                .iter()
+                .enumerate()
+                .map(|(j, decl)| TypeArg::use_var(j, decl.clone())),
+        );
+        let body = self.body.substitute(self.exts, &args);
+        let mut constraints = Vec::with_capacity(self.constraints.len());
+        for c in &self.constraints {
+            let indices = c.indices();
+            if indices.iter().all(|&i| i >= prefix.len()) {
+                // Entirely about the remaining params - carry it forward for
+                // the returned scheme to check once the rest of the args are
+                // known.
+                constraints.push(c.reindexed(prefix.len()));
+            } else if indices.iter().all(|&i| i < prefix.len()) {
+                // Entirely consumed by `prefix` - this is our last chance to
+                // check it, since the returned scheme doesn't carry it.
+                if !constraint_satisfied(c, &args) {
+                    return Err(SignatureError::ConstraintUnsatisfied {
+                        constraint: c.clone(),
+                        args: args.clone(),
+                    });
+                }
+            } else {
+                // Spans the prefix/remaining boundary - reindexing would
+                // silently produce a constraint over the wrong params, so
+                // reject rather than guess.
+                return Err(SignatureError::InvalidTypeArgs);
+            }
+        }
+        debug_assert_eq!(remaining_params.len(), num_remaining);
+        Self::new_with_constraints(remaining_params.to_vec(), body, constraints, self.exts)
+    }
+}
+
+/// Checks `arg` against `param`, descending into a [TypeArg::Type]'s
+/// [CustomType](crate::types::CustomType) args (against the declared
+/// [TypeDef](super::TypeDef)'s own params, looked up in `exts`) before
+/// falling back to [check_type_args] for the pair itself. `path` accumulates
+/// the [ArgPathElem]s taken to reach `arg` so far, for
+/// [SignatureError::ArgMismatchAt].
+fn check_type_arg_located(
+    arg: &TypeArg,
+    param: &TypeParam,
+    path: &mut Vec<ArgPathElem>,
+    exts: &ExtensionRegistry,
+) -> Result<(), SignatureError> {
+    if let (TypeArg::Type { ty }, TypeParam::Type(_)) = (arg, param) {
+        if let TypeEnum::Extension(c) = ty.as_type_enum() {
+            if let Some(def) = exts.get(c.extension()).and_then(|e| e.get_type(c.name())) {
+                for (index, (nested_arg, nested_param)) in
+                    c.args().iter().zip(def.params()).enumerate()
+                {
+                    path.push(ArgPathElem::CustomTypeArg {
+                        def: (c.extension().clone(), c.name().clone()),
+                        index,
+                    });
+                    check_type_arg_located(nested_arg, nested_param, path, exts)?;
+                    path.pop();
+                }
+            }
+        }
+    }
+    check_type_args(std::slice::from_ref(arg), std::slice::from_ref(param)).map_err(|_| {
+        SignatureError::ArgMismatchAt {
+            path: path.clone(),
+            expected: param.clone(),
+            found: arg.clone(),
+        }
+    })
 }
 
 impl<'a> CustomSignatureFunc for OpDefTypeScheme<'a> {
@@ -52,7 +419,9 @@ impl<'a> CustomSignatureFunc for OpDefTypeScheme<'a> {
         args: &[TypeArg],
         _misc: &std::collections::HashMap<String, serde_yaml::Value>,
     ) -> Result<FunctionType, SignatureError> {
-        check_type_args(args, &self.params).map_err(SignatureError::TypeArgMismatch)?;
+        self.check_args_located(args)?;
+        self.check_variance(args)?;
+        self.check_constraints(args)?;
         Ok(self.body.substitute(self.exts, args))
     }
 }
@@ -73,7 +442,7 @@ mod test {
     use crate::types::{CustomType, FunctionType, Type, TypeBound};
     use crate::Extension;
 
-    use super::OpDefTypeScheme;
+    use super::{ArgPathElem, OpDefTypeScheme, ParamConstraint};
 
     #[test]
     fn test_opaque() -> Result<(), SignatureError> {
@@ -154,12 +523,11 @@ mod test {
         );
         assert_eq!(
             wrong_args,
-            Err(SignatureError::TypeArgMismatch(
-                TypeArgError::TypeMismatch {
-                    param: typarams[0].clone(),
-                    arg: TypeArg::BoundedNat { n: 5 }
-                }
-            ))
+            Err(SignatureError::ArgMismatchAt {
+                path: vec![ArgPathElem::Input(0)],
+                expected: typarams[0].clone(),
+                found: TypeArg::BoundedNat { n: 5 }
+            })
         );
 
         // (Try to) make a schema with bad args
@@ -296,4 +664,29 @@ mod test {
         )?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_partial_checks_consumed_constraints() -> Result<(), SignatureError> {
+        let reg: ExtensionRegistry = [PRELUDE.to_owned()].into();
+        let scheme = OpDefTypeScheme::new_with_constraints(
+            [TypeParam::max_nat(), TypeParam::max_nat()],
+            FunctionType::new(vec![USIZE_T], vec![USIZE_T]),
+            vec![ParamConstraint::NatEq(0, 1)],
+            &reg,
+        )?;
+        let n = |v: u64| TypeArg::BoundedNat { n: v };
+
+        // Both params of the NatEq constraint are in the consumed prefix and
+        // it holds - fine to drop, nothing is left to check it against.
+        scheme.partial(&[n(3), n(3)])?;
+
+        // Still both in the consumed prefix, but this time it's violated -
+        // the returned scheme never sees this constraint again, so `partial`
+        // itself must catch it instead of silently dropping it.
+        assert!(matches!(
+            scheme.partial(&[n(3), n(4)]),
+            Err(SignatureError::ConstraintUnsatisfied { .. })
+        ));
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@
 use std::ops::{Add, Div, Mul, Neg, Sub};
 
 use cgmath::num_traits::ToPrimitive;
+use cgmath::{InnerSpace, Rad, Rotation3, Vector3};
 use num_rational::Rational64;
 use smol_str::SmolStr;
 
@@ -27,9 +28,24 @@ pub fn resource() -> Resource {
     resource.add_type(Type::Quaternion.into());
 
     resource.add_opaque_op(AngleAdd.into());
+    resource.add_opaque_op(AngleMul.into());
+    resource.add_opaque_op(AngleNeg.into());
+    resource.add_opaque_op(QuatMul.into());
+    resource.add_opaque_op(RxF64.into());
+    resource.add_opaque_op(RzF64.into());
+    resource.add_opaque_op(TK1.into());
+    resource.add_opaque_op(Rotation.into());
+    resource.add_opaque_op(ToRotation.into());
     resource
 }
 
+/// The [`ResourceSet`] containing just this resource, shared by every op's
+/// `resources()` so we don't rebuild it on every call.
+fn rotations_resource_set() -> &'static ResourceSet {
+    static SET: std::sync::OnceLock<ResourceSet> = std::sync::OnceLock::new();
+    SET.get_or_init(|| ResourceSet::singleton(&resource_id()))
+}
+
 /// Custom types defined by this extension.
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum Type {
@@ -101,47 +117,186 @@ impl CustomOp for AngleAdd {
     }
 
     fn resources(&self) -> &ResourceSet {
-        // TODO: Don't return a reference? We need to initialize the resource set.
-        todo!()
-    }
-}
-
-//
-// TODO:
-//
-// operations:
-//
-//     AngleAdd,
-//     AngleMul,
-//     AngleNeg,
-//     QuatMul,
-//     RxF64,
-//     RzF64,
-//     TK1,
-//     Rotation,
-//     ToRotation,
-//
-//
-//
-// signatures:
-//
-//             LeafOp::AngleAdd | LeafOp::AngleMul => Signature::new_linear([Type::Angle]),
-//             LeafOp::QuatMul => Signature::new_linear([Type::Quat64]),
-//             LeafOp::AngleNeg => Signature::new_linear([Type::Angle]),
-//             LeafOp::RxF64 | LeafOp::RzF64 => {
-//                 Signature::new_df([Type::Qubit], [Type::Angle])
-//             }
-//             LeafOp::TK1 => Signature::new_df(vec![Type::Qubit], vec![Type::Angle; 3]),
-//             LeafOp::Rotation => Signature::new_df([Type::Qubit], [Type::Quat64]),
-//             LeafOp::ToRotation => Signature::new_df(
-//                 [
-//                     Type::Angle,
-//                     Type::F64,
-//                     Type::F64,
-//                     Type::F64,
-//                 ],
-//                 [Type::Quat64],
-//             ),
+        rotations_resource_set()
+    }
+}
+
+/// `Angle -> Angle`: multiplies two angles together (mod a full turn, see
+/// [`AngleValue`] folding).
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AngleMul;
+
+#[typetag::serde]
+impl CustomOp for AngleMul {
+    fn name(&self) -> SmolStr {
+        "AngleMul".into()
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new_linear(vec![SimpleType::Classic(Type::Angle.classic_type())])
+    }
+
+    fn resources(&self) -> &ResourceSet {
+        rotations_resource_set()
+    }
+}
+
+/// `Angle -> Angle`: negates an angle.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AngleNeg;
+
+#[typetag::serde]
+impl CustomOp for AngleNeg {
+    fn name(&self) -> SmolStr {
+        "AngleNeg".into()
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new_linear(vec![SimpleType::Classic(Type::Angle.classic_type())])
+    }
+
+    fn resources(&self) -> &ResourceSet {
+        rotations_resource_set()
+    }
+}
+
+/// `Quaternion -> Quaternion`: composes two rotations by quaternion
+/// multiplication.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct QuatMul;
+
+#[typetag::serde]
+impl CustomOp for QuatMul {
+    fn name(&self) -> SmolStr {
+        "QuatMul".into()
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new_linear(vec![SimpleType::Classic(Type::Quaternion.classic_type())])
+    }
+
+    fn resources(&self) -> &ResourceSet {
+        rotations_resource_set()
+    }
+}
+
+/// `[Qubit, Angle] -> [Qubit]`: rotates a qubit about the X axis by the given
+/// angle.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RxF64;
+
+#[typetag::serde]
+impl CustomOp for RxF64 {
+    fn name(&self) -> SmolStr {
+        "RxF64".into()
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new_df(
+            vec![SimpleType::Qubit, SimpleType::Classic(Type::Angle.classic_type())],
+            vec![SimpleType::Qubit],
+        )
+    }
+
+    fn resources(&self) -> &ResourceSet {
+        rotations_resource_set()
+    }
+}
+
+/// `[Qubit, Angle] -> [Qubit]`: rotates a qubit about the Z axis by the given
+/// angle.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RzF64;
+
+#[typetag::serde]
+impl CustomOp for RzF64 {
+    fn name(&self) -> SmolStr {
+        "RzF64".into()
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new_df(
+            vec![SimpleType::Qubit, SimpleType::Classic(Type::Angle.classic_type())],
+            vec![SimpleType::Qubit],
+        )
+    }
+
+    fn resources(&self) -> &ResourceSet {
+        rotations_resource_set()
+    }
+}
+
+/// `[Qubit, Angle, Angle, Angle] -> [Qubit]`: the TK1 single-qubit gate,
+/// parameterised by three Euler angles.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TK1;
+
+#[typetag::serde]
+impl CustomOp for TK1 {
+    fn name(&self) -> SmolStr {
+        "TK1".into()
+    }
+
+    fn signature(&self) -> Signature {
+        let angle = SimpleType::Classic(Type::Angle.classic_type());
+        Signature::new_df(
+            vec![SimpleType::Qubit, angle.clone(), angle.clone(), angle],
+            vec![SimpleType::Qubit],
+        )
+    }
+
+    fn resources(&self) -> &ResourceSet {
+        rotations_resource_set()
+    }
+}
+
+/// `[Qubit, Quaternion] -> [Qubit]`: applies an arbitrary rotation,
+/// represented as a quaternion, to a qubit.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Rotation;
+
+#[typetag::serde]
+impl CustomOp for Rotation {
+    fn name(&self) -> SmolStr {
+        "Rotation".into()
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new_df(
+            vec![SimpleType::Qubit, SimpleType::Classic(Type::Quaternion.classic_type())],
+            vec![SimpleType::Qubit],
+        )
+    }
+
+    fn resources(&self) -> &ResourceSet {
+        rotations_resource_set()
+    }
+}
+
+/// `[Angle, Angle, Angle] -> [Quaternion]`: builds the quaternion
+/// representing the Euler-angle (TK1-style) rotation given by its three
+/// angle arguments.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ToRotation;
+
+#[typetag::serde]
+impl CustomOp for ToRotation {
+    fn name(&self) -> SmolStr {
+        "ToRotation".into()
+    }
+
+    fn signature(&self) -> Signature {
+        let angle = SimpleType::Classic(Type::Angle.classic_type());
+        Signature::new_df(
+            vec![angle.clone(), angle.clone(), angle],
+            vec![SimpleType::Classic(Type::Quaternion.classic_type())],
+        )
+    }
+
+    fn resources(&self) -> &ResourceSet {
+        rotations_resource_set()
+    }
+}
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Serialize, serde::Deserialize)]
 #[cfg_attr(feature = "pyo3", pyclass(name = "Rational"))]
@@ -201,6 +356,92 @@ impl AngleValue {
     pub fn radians(&self) -> f64 {
         self.to_f64() * std::f64::consts::PI
     }
+
+    /// Reduces this angle modulo a full turn (`2`, since the value is a
+    /// multiple of pi) into the canonical half-open range `[0, 2)`.
+    ///
+    /// `Rational` angles stay exact: `p/q` is normalized by taking `p mod 2q`
+    /// with a Euclidean (always non-negative) remainder, then reconstructing
+    /// `p/q` from the reduced numerator. `F64` angles are folded with
+    /// `rem_euclid(2.0)`, which is the floating-point equivalent.
+    pub fn normalized(&self) -> Self {
+        match self {
+            AngleValue::F64(x) => AngleValue::F64(x.rem_euclid(2.0)),
+            AngleValue::Rational(Rational(r)) => {
+                let q = *r.denom();
+                let p = r.numer().rem_euclid(2 * q);
+                AngleValue::Rational(Rational(Rational64::new(p, q)))
+            }
+        }
+    }
+
+    /// Whether this angle is the identity rotation, i.e. normalizes to `0`.
+    pub fn is_zero(&self) -> bool {
+        match self.normalized() {
+            AngleValue::F64(x) => x == 0.0,
+            AngleValue::Rational(Rational(r)) => *r.numer() == 0,
+        }
+    }
+}
+
+/// Constant-folds a single [`AngleAdd`]/[`AngleMul`]/[`AngleNeg`] operation
+/// application, whose operands are already constant angles, into a
+/// normalized [`Constant::Angle`]. Nested applications are not walked: each
+/// call folds exactly one op, so `AngleAdd(AngleAdd(a, b), AngleNeg(a))`
+/// reduces to `b` (modulo a full turn) only once the inner applications have
+/// been folded to angles first. Returns `None` for any other op name or
+/// operand count, so a rewrite pass can try this first and fall through
+/// otherwise.
+pub fn fold_angle_op(op_name: &str, inputs: &[AngleValue]) -> Option<Constant> {
+    let folded = match (op_name, inputs) {
+        ("AngleAdd", [a, b]) => *a + *b,
+        ("AngleMul", [a, b]) => *a * *b,
+        ("AngleNeg", [a]) => -*a,
+        _ => return None,
+    };
+    Some(Constant::Angle(folded.normalized()))
+}
+
+/// Builds the unit quaternion representing a rotation by `angle` (in the
+/// same "multiples of pi" convention as [`AngleValue`]) about `axis`.
+/// `axis` need not be normalized.
+pub fn quat_from_axis_angle(axis: [f64; 3], angle: &AngleValue) -> cgmath::Quaternion<f64> {
+    let axis = Vector3::new(axis[0], axis[1], axis[2]).normalize();
+    cgmath::Quaternion::from_axis_angle(axis, Rad(angle.radians()))
+}
+
+/// Builds the quaternion for the rotation given by its Euler-angle (TK1
+/// style, X-Y-Z) decomposition: rotate about X by `angles[0]`, then about Y
+/// by `angles[1]`, then about Z by `angles[2]`.
+pub fn quat_from_euler(angles: [&AngleValue; 3]) -> cgmath::Quaternion<f64> {
+    let rx = cgmath::Quaternion::from_angle_x(Rad(angles[0].radians()));
+    let ry = cgmath::Quaternion::from_angle_y(Rad(angles[1].radians()));
+    let rz = cgmath::Quaternion::from_angle_z(Rad(angles[2].radians()));
+    (rz * ry * rx).normalize()
+}
+
+/// Composes two rotations by quaternion multiplication, renormalizing to
+/// correct for any drift accumulated across a chain of multiplications.
+pub fn quat_mul(a: cgmath::Quaternion<f64>, b: cgmath::Quaternion<f64>) -> cgmath::Quaternion<f64> {
+    (a * b).normalize()
+}
+
+/// Spherical linear interpolation between two unit quaternions at `t`
+/// (`0` yields `q0`, `1` yields `q1`), taking the shorter of the two arcs
+/// between them.
+pub fn slerp(q0: cgmath::Quaternion<f64>, q1: cgmath::Quaternion<f64>, t: f64) -> cgmath::Quaternion<f64> {
+    let d = q0.dot(q1);
+    let (q1, d) = if d < 0.0 { (-q1, -d) } else { (q1, d) };
+
+    // Close together: linear interpolation avoids dividing by a near-zero
+    // sin(theta).
+    if d > 1.0 - 1e-6 {
+        return (q0 * (1.0 - t) + q1 * t).normalize();
+    }
+
+    let theta = d.acos();
+    let sin_theta = theta.sin();
+    (q0 * ((1.0 - t) * theta).sin() + q1 * (t * theta).sin()) * (1.0 / sin_theta)
 }
 
 impl Add for AngleValue {
@@ -1,18 +1,26 @@
 //! The Hugr data structure.
 //!
-//! TODO: metadata
+//! See [`attributes`] for per-node metadata storage.
 #![allow(dead_code)]
 
-use portgraph::{Hierarchy, NodeIndex, PortGraph, SecondaryMap};
+use portgraph::{Direction, Hierarchy, NodeIndex, PortGraph, SecondaryMap};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::extension::EMPTY_REG;
 use crate::ops::{ModuleOp, OpType};
 use crate::rewrite::{Rewrite, RewriteError};
+use crate::types::Type;
 
+pub mod attributes;
+pub mod cbor;
+pub mod monomorphize;
 pub mod serialize;
+pub mod structural_hash;
+pub mod validate;
 
 /// The Hugr data structure.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Hugr {
     /// The graph encoding the adjacency structure of the HUGR.
     pub(crate) graph: PortGraph,
@@ -61,13 +69,38 @@ impl Hugr {
         node
     }
 
-    /// Connect two nodes at the given ports.
+    /// Connect two nodes at the given ports with a data edge, rejecting the
+    /// link if the source's output type at `src_port` is not a subtype of
+    /// the destination's input type at `dst_port`.
+    ///
+    /// For order/state edges, which carry no type, use
+    /// [`Self::connect_order`] instead.
     pub fn connect(
         &mut self,
         src: NodeIndex,
         src_port: usize,
         dst: NodeIndex,
         dst_port: usize,
+    ) -> Result<(), HugrError> {
+        let src_ty = self.op_types[src].signature().output[src_port].clone();
+        let dst_ty = self.op_types[dst].signature().input[dst_port].clone();
+        if !src_ty.is_subtype_of(&dst_ty, &EMPTY_REG) {
+            return Err(HugrError::IncompatibleEdge { src_ty, dst_ty });
+        }
+        self.graph.link_nodes(src, src_port, dst, dst_port)?;
+        Ok(())
+    }
+
+    /// Connect two nodes at the given ports with an order edge.
+    ///
+    /// Unlike [`Self::connect`], order edges carry no value, so the ports'
+    /// types (if any) are not checked for compatibility.
+    pub fn connect_order(
+        &mut self,
+        src: NodeIndex,
+        src_port: usize,
+        dst: NodeIndex,
+        dst_port: usize,
     ) -> Result<(), HugrError> {
         self.graph.link_nodes(src, src_port, dst, dst_port)?;
         Ok(())
@@ -108,20 +141,156 @@ impl Hugr {
     }
 
     /// Check the validity of the HUGR.
+    ///
+    /// This runs three independent passes: port/signature consistency,
+    /// edge type-compatibility, and well-formedness of the hierarchy.
     pub fn validate(&self) -> Result<(), ValidationError> {
-        // TODO
+        self.validate_ports()?;
+        self.validate_edges()?;
+        self.validate_hierarchy()?;
+        Ok(())
+    }
+
+    /// Checks that every node's actual port counts in [`Self::graph`] match
+    /// the in/out arity of its op's signature.
+    fn validate_ports(&self) -> Result<(), ValidationError> {
+        for node in self.graph.nodes_iter() {
+            self.validate_node_ports(node)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that every link in [`Self::graph`] connects an output and an
+    /// input whose types are compatible (the source's type must be a subtype
+    /// of the destination's).
+    fn validate_edges(&self) -> Result<(), ValidationError> {
+        for src in self.graph.nodes_iter() {
+            self.validate_node_edges(src)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that the hierarchy is well-formed: the root is a
+    /// [`ModuleOp::Root`], and every other node has a parent.
+    fn validate_hierarchy(&self) -> Result<(), ValidationError> {
+        for node in self.graph.nodes_iter() {
+            self.validate_node_hierarchy(node)?;
+        }
         Ok(())
     }
 
+    /// Checks that `node`'s actual port counts in [`Self::graph`] match the
+    /// in/out arity of its op's signature. The single-node scope that
+    /// [`Self::validate_ports`] sums over every node, and that
+    /// [`validate::incremental`] reruns for just the nodes an edit may have
+    /// affected instead of the whole graph.
+    pub(crate) fn validate_node_ports(&self, node: NodeIndex) -> Result<(), ValidationError> {
+        let sig = self.op_types[node].signature();
+        let expected = (sig.input.len(), sig.output.len());
+        let found = (
+            self.graph.num_ports(node, Direction::Incoming),
+            self.graph.num_ports(node, Direction::Outgoing),
+        );
+        if expected != found {
+            return Err(ValidationError::SignaturePortMismatch {
+                node,
+                expected,
+                found,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that every outgoing link from `node` connects to an input
+    /// whose type is compatible. See [`Self::validate_node_ports`] for why
+    /// this is split out from [`Self::validate_edges`].
+    pub(crate) fn validate_node_edges(&self, src: NodeIndex) -> Result<(), ValidationError> {
+        let src_sig = self.op_types[src].signature();
+        for src_offset in self.graph.port_offsets(src, Direction::Outgoing) {
+            let src_ty = &src_sig.output[src_offset.index()];
+            let src_port = self
+                .graph
+                .port_index(src, src_offset)
+                .expect("offset came from this node");
+            for (_, link) in self.graph.port_links(src_port) {
+                let dst_port = link.port();
+                let dst = self
+                    .graph
+                    .port_node(dst_port)
+                    .expect("linked ports belong to a node");
+                let dst_offset = self
+                    .graph
+                    .port_offset(dst_port)
+                    .expect("linked ports have an offset");
+                let dst_sig = self.op_types[dst].signature();
+                let dst_ty = &dst_sig.input[dst_offset.index()];
+                if !src_ty.is_subtype_of(dst_ty, &EMPTY_REG) {
+                    return Err(ValidationError::EdgeTypeMismatch {
+                        src,
+                        src_port: src_offset.index(),
+                        dst,
+                        dst_port: dst_offset.index(),
+                        src_ty: src_ty.clone(),
+                        dst_ty: dst_ty.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that `node` is well-formed in the hierarchy: if it's the
+    /// root, that it's a [`ModuleOp::Root`]; otherwise, that it has a
+    /// parent. See [`Self::validate_node_ports`] for why this is split out
+    /// from [`Self::validate_hierarchy`].
+    pub(crate) fn validate_node_hierarchy(&self, node: NodeIndex) -> Result<(), ValidationError> {
+        if node == self.root {
+            return if self.op_types[self.root] != OpType::Module(ModuleOp::Root) {
+                Err(ValidationError::RootNotModule(self.root))
+            } else {
+                Ok(())
+            };
+        }
+        if self.hierarchy.parent(node).is_none() {
+            return Err(ValidationError::DanglingNode(node));
+        }
+        Ok(())
+    }
+
+    /// All nodes directly linked to one of `node`'s ports, in either
+    /// direction. A change to `node` (its op, or an edge it's part of) can
+    /// affect whether one of these neighbours' own edges still validates,
+    /// since [`Self::validate_node_edges`] checks a link using both
+    /// endpoints' signatures - so [`validate::incremental::CachedValidator`]
+    /// treats a neighbour as dirty too whenever `node` is.
+    pub(crate) fn local_neighbours(&self, node: NodeIndex) -> impl Iterator<Item = NodeIndex> + '_ {
+        [Direction::Incoming, Direction::Outgoing]
+            .into_iter()
+            .flat_map(move |dir| self.graph.port_offsets(node, dir))
+            .filter_map(move |offset| self.graph.port_index(node, offset))
+            .flat_map(move |port| self.graph.port_links(port))
+            .filter_map(move |(_, link)| self.graph.port_node(link.port()))
+    }
+
     pub fn root(&self) -> NodeIndex {
         self.root
     }
+
+    /// Returns the operation type of a node.
+    pub fn get_optype(&self, node: NodeIndex) -> &OpType {
+        &self.op_types[node]
+    }
+
+    /// Returns the children of `node` in the hierarchy, in order.
+    pub fn children(&self, node: NodeIndex) -> portgraph::hierarchy::Children<'_> {
+        self.hierarchy.children(node)
+    }
 }
 
 /// Errors that can occur while manipulating a Hugr.
 ///
 /// TODO: Better descriptions, not just re-exporting portgraph errors.
-#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[derive(Debug, Clone, PartialEq, Error)]
 #[non_exhaustive]
 pub enum HugrError {
     /// An error occurred while connecting nodes.
@@ -130,7 +299,55 @@ pub enum HugrError {
     /// An error occurred while manipulating the hierarchy.
     #[error("An error occurred while manipulating the hierarchy.")]
     HierarchyError(#[from] portgraph::hierarchy::AttachError),
+    /// A data edge was rejected because its source and destination types
+    /// are incompatible.
+    #[error("Cannot connect an edge of type {src_ty} to a port of type {dst_ty}")]
+    IncompatibleEdge {
+        /// The source port's type.
+        src_ty: Type,
+        /// The destination port's type.
+        dst_ty: Type,
+    },
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Error)]
-pub enum ValidationError {}
+/// Errors that can occur while validating a Hugr.
+#[derive(Debug, Clone, PartialEq, Error)]
+#[non_exhaustive]
+pub enum ValidationError {
+    /// A node's actual (incoming, outgoing) port counts don't match the
+    /// arity declared by its op's signature.
+    #[error("Node {node:?} has ports {found:?} but its signature declares {expected:?}")]
+    SignaturePortMismatch {
+        /// The offending node.
+        node: NodeIndex,
+        /// The (input, output) arity declared by the node's signature.
+        expected: (usize, usize),
+        /// The (input, output) port counts actually present in the graph.
+        found: (usize, usize),
+    },
+    /// An edge connects an output and an input whose types are incompatible.
+    #[error(
+        "Edge from {src:?} port {src_port} to {dst:?} port {dst_port} has incompatible types: \
+         {src_ty} is not a subtype of {dst_ty}"
+    )]
+    EdgeTypeMismatch {
+        /// The source node of the edge.
+        src: NodeIndex,
+        /// The offending output port.
+        src_port: usize,
+        /// The destination node of the edge.
+        dst: NodeIndex,
+        /// The offending input port.
+        dst_port: usize,
+        /// The type of the source port.
+        src_ty: Type,
+        /// The type of the destination port.
+        dst_ty: Type,
+    },
+    /// The root node is not a [`ModuleOp::Root`].
+    #[error("The root node {0:?} is not a module root")]
+    RootNotModule(NodeIndex),
+    /// A non-root node has no parent in the hierarchy.
+    #[error("Node {0:?} has no parent in the hierarchy")]
+    DanglingNode(NodeIndex),
+}
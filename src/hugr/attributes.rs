@@ -0,0 +1,1128 @@
+//! Per-node attribute storage, filling in the `TODO: metadata` noted on
+//! [`crate::hugr`].
+//!
+//! An [`Attr`] is some piece of data that can be attached to [`Node`]s (e.g.
+//! a name, a source location, a cost estimate); its [`AttrStore`] is the
+//! container that actually holds the `Node -> Attr` mapping, and an
+//! [`AttrGroup`] is a type-erased collection of stores, one per [`Attr`]
+//! type, so a [`crate::hugr::Hugr`] can carry an open-ended set of
+//! attributes without a field for each one.
+//!
+//! Each store is kept behind an [`AttrCell`] rather than a [`std::cell::RefCell`]:
+//! a plain `RefCell` is `!Sync`, so an `AttrGroup` containing one could never
+//! be shared across threads (e.g. read by several passes running
+//! concurrently over the same HUGR). [`AttrCell`] tracks its borrow state
+//! with an [`AtomicUsize`] instead, giving the same "many readers xor one
+//! writer" discipline but without requiring exclusive access to the thread.
+//! Unlike [`std::cell::RefCell::borrow`], conflicting borrows here are
+//! reported as `None` rather than a panic - see [`AttrGroup::try_borrow`].
+//!
+//! The group's own `TypeId -> AttrCell` map is behind an [`RwLock`] rather
+//! than requiring `&mut AttrGroup`, so [`AttrGroup::with`]/[`AttrGroup::with_mut`]
+//! can lazily materialise a [`Default`] store the first time an attribute is
+//! touched - without forcing every caller to thread a `&mut AttrGroup`
+//! through just to `register` a store up front. Each [`AttrCell`] is kept
+//! behind an [`Arc`] so that a lookup only needs to hold the map lock long
+//! enough to find or insert the entry; the borrow itself is then scoped to
+//! the individual [`AttrCell`], same as [`AttrGroup::borrow`].
+use std::any::{Any, TypeId};
+use std::cell::UnsafeCell;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::ops::{Deref, DerefMut, Index, IndexMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+use serde::de::Error as _;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::Node;
+
+/// A borrow-state shared between every [`AttrRef`]/[`AttrRefMut`] handed out
+/// from the same [`AttrCell`]: `0` means unborrowed, a positive count is the
+/// number of live shared borrows, and [`WRITING`] marks a live exclusive
+/// borrow.
+const WRITING: usize = usize::MAX;
+
+#[derive(Default)]
+struct BorrowState(AtomicUsize);
+
+impl BorrowState {
+    fn try_read(&self) -> bool {
+        loop {
+            let current = self.0.load(Ordering::Acquire);
+            if current == WRITING {
+                return false;
+            }
+            if self
+                .0
+                .compare_exchange_weak(current, current + 1, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+            {
+                return true;
+            }
+        }
+    }
+
+    fn release_read(&self) {
+        self.0.fetch_sub(1, Ordering::Release);
+    }
+
+    fn try_write(&self) -> bool {
+        self.0
+            .compare_exchange(0, WRITING, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
+    fn release_write(&self) {
+        self.0.store(0, Ordering::Release);
+    }
+}
+
+/// An `AtomicUsize`-guarded equivalent of [`std::cell::RefCell`]: the same
+/// "many readers xor one writer" borrow discipline, but checkable (and
+/// enforceable) from multiple threads at once, so a type wrapping it can be
+/// [`Sync`] rather than being stuck `!Sync` like `RefCell` always is.
+///
+/// Conflicting borrows return `None` (via [`Self::try_borrow`] /
+/// [`Self::try_borrow_mut`]) rather than panicking.
+struct AttrCell<T> {
+    state: BorrowState,
+    value: UnsafeCell<T>,
+}
+
+// SAFETY: `AttrCell` only ever exposes `&T`/`&mut T` behind the exclusion
+// enforced by `BorrowState`'s atomic compare-exchanges, the same invariant
+// `std::sync::RwLock` relies on for these same bounds.
+unsafe impl<T: ?Sized + Send> Send for AttrCell<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for AttrCell<T> {}
+
+impl<T> AttrCell<T> {
+    fn new(value: T) -> Self {
+        Self {
+            state: BorrowState::default(),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    fn get_mut(&mut self) -> &mut T {
+        // `&mut self` already guarantees exclusive access; no need to touch
+        // the atomic borrow state.
+        self.value.get_mut()
+    }
+
+    fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+/// Tries to take a shared borrow of `cell`, returning a guard that owns its
+/// own clone of the [`Arc`] (rather than borrowing from whoever called this)
+/// so the caller doesn't need to keep anything else alive for the guard's
+/// lifetime - e.g. a map lock guarding the [`Arc`] itself can be dropped
+/// right after this returns.
+fn try_borrow_cell<T>(cell: &Arc<AttrCell<T>>) -> Option<AttrCellRef<T>> {
+    cell.state.try_read().then(|| AttrCellRef {
+        cell: Arc::clone(cell),
+    })
+}
+
+/// See [`try_borrow_cell`]; the exclusive-borrow counterpart.
+fn try_borrow_cell_mut<T>(cell: &Arc<AttrCell<T>>) -> Option<AttrCellRefMut<T>> {
+    cell.state.try_write().then(|| AttrCellRefMut {
+        cell: Arc::clone(cell),
+    })
+}
+
+struct AttrCellRef<T> {
+    cell: Arc<AttrCell<T>>,
+}
+
+impl<T> Deref for AttrCellRef<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: `BorrowState::try_read` succeeded, so no exclusive borrow
+        // is live until this guard is dropped.
+        unsafe { &*self.cell.value.get() }
+    }
+}
+
+impl<T> Drop for AttrCellRef<T> {
+    fn drop(&mut self) {
+        self.cell.state.release_read();
+    }
+}
+
+struct AttrCellRefMut<T> {
+    cell: Arc<AttrCell<T>>,
+}
+
+impl<T> Deref for AttrCellRefMut<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: `BorrowState::try_write` succeeded, so this is the only
+        // live borrow until this guard is dropped.
+        unsafe { &*self.cell.value.get() }
+    }
+}
+
+impl<T> DerefMut for AttrCellRefMut<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: as above.
+        unsafe { &mut *self.cell.value.get() }
+    }
+}
+
+impl<T> Drop for AttrCellRefMut<T> {
+    fn drop(&mut self) {
+        self.cell.state.release_write();
+    }
+}
+
+type AttrCellHandle = Arc<AttrCell<Box<dyn AttrStoreDyn>>>;
+
+/// Group of attribute stores.
+#[derive(Default)]
+pub struct AttrGroup {
+    stores: RwLock<HashMap<TypeId, AttrCellHandle>>,
+}
+
+impl AttrGroup {
+    // PERFORMANCE: We know that the downcasts in each method must always
+    // succeed and therefore would not need to perform the check. If the
+    // checks turn out to be slow, we can use the unsafe downcast.
+
+    /// Creates an empty [`AttrGroup`].
+    pub fn new() -> Self {
+        Self {
+            stores: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Returns an immutable reference to the store for an attribute.
+    ///
+    /// # Panics
+    ///
+    /// - When the attribute is already mutably borrowed.
+    /// - When the attribute type is not present in the group.
+    #[inline]
+    pub fn borrow<T: Attr>(&self) -> AttrRef<T> {
+        self.try_borrow()
+            .expect("unknown attribute type, or already mutably borrowed")
+    }
+
+    /// Returns an immutable reference to the store for an attribute, or
+    /// `None` when the attribute is not present in the group, or when it is
+    /// already mutably borrowed elsewhere.
+    pub fn try_borrow<T: Attr>(&self) -> Option<AttrRef<T>> {
+        let cell = self.stores.read().unwrap().get(&TypeId::of::<T>())?.clone();
+        let cell_ref = try_borrow_cell(&cell)?;
+        Some(AttrRef(cell_ref, std::marker::PhantomData))
+    }
+
+    /// Returns a mutable reference to the store for an attribute.
+    ///
+    /// # Panics
+    ///
+    /// - When the attribute is already borrowed (mutably or immutably).
+    /// - When the attribute type is not present in the group.
+    #[inline]
+    pub fn borrow_mut<T: Attr>(&self) -> AttrRefMut<T> {
+        self.try_borrow_mut()
+            .expect("unknown attribute type, or already borrowed")
+    }
+
+    /// Returns a mutable reference to the store for an attribute, or `None`
+    /// when the attribute is not present in the group, or when it is already
+    /// borrowed (mutably or immutably) elsewhere.
+    pub fn try_borrow_mut<T: Attr>(&self) -> Option<AttrRefMut<T>> {
+        let cell = self.stores.read().unwrap().get(&TypeId::of::<T>())?.clone();
+        let cell_ref = try_borrow_cell_mut(&cell)?;
+        Some(AttrRefMut(cell_ref, std::marker::PhantomData))
+    }
+
+    /// Borrows several attribute stores at once, as declared by `F` (a tuple
+    /// of [`Read`]/[`Write`] markers, e.g. `group.fetch::<(Read<Foo>, Write<Bar>)>()`).
+    ///
+    /// Checking every requested [`TypeId`] for duplicates up front - rather
+    /// than acquiring each guard one [`Self::borrow`]/[`Self::borrow_mut`]
+    /// call at a time - means a caller declaring its full read/write set this
+    /// way can never end up racing two live mutable borrows of the same
+    /// store against each other.
+    ///
+    /// # Panics
+    ///
+    /// - When the same attribute type is requested more than once (whether
+    ///   as [`Read`], [`Write`], or both).
+    /// - When any requested attribute is missing or already incompatibly
+    ///   borrowed - see [`Self::borrow`]/[`Self::borrow_mut`].
+    pub fn fetch<F: AttrFetch>(&self) -> F::Output {
+        let ids = F::type_ids();
+        for (i, a) in ids.iter().enumerate() {
+            for b in &ids[i + 1..] {
+                assert!(
+                    a != b,
+                    "conflicting attribute access: the same attribute type was requested twice in one fetch"
+                );
+            }
+        }
+        F::fetch(self)
+    }
+
+    /// Runs `f` on the store for `T`, lazily creating an empty (`Default`)
+    /// store first if one isn't already registered - so touching an
+    /// attribute nobody has [`register`](Self::register)ed yet just sees an
+    /// empty store rather than panicking the way [`Self::borrow`] would.
+    ///
+    /// # Panics
+    ///
+    /// When the attribute is already mutably borrowed elsewhere.
+    pub fn with<T: Attr, R>(&self, f: impl FnOnce(&T::Store) -> R) -> R {
+        let cell = self.get_or_create_cell::<T>();
+        let cell_ref = try_borrow_cell(&cell).expect("attribute store already mutably borrowed");
+        f((*cell_ref).downcast_ref().unwrap())
+    }
+
+    /// Runs `f` on the store for `T`, lazily creating an empty (`Default`)
+    /// store first if one isn't already registered. See [`Self::with`].
+    ///
+    /// # Panics
+    ///
+    /// When the attribute is already borrowed (mutably or immutably) elsewhere.
+    pub fn with_mut<T: Attr, R>(&self, f: impl FnOnce(&mut T::Store) -> R) -> R {
+        let cell = self.get_or_create_cell::<T>();
+        let mut cell_ref = try_borrow_cell_mut(&cell).expect("attribute store already borrowed");
+        f((*cell_ref).downcast_mut().unwrap())
+    }
+
+    /// The cell for `T`'s store, inserting a freshly [`Default`]-constructed
+    /// one first if it isn't already present. Takes the map's write lock
+    /// only for that insertion (and only if a fast read-locked lookup didn't
+    /// already find the entry); the returned handle's own borrow is then
+    /// independent of the map lock.
+    fn get_or_create_cell<T: Attr>(&self) -> AttrCellHandle {
+        if let Some(cell) = self.stores.read().unwrap().get(&TypeId::of::<T>()) {
+            return cell.clone();
+        }
+        self.stores
+            .write()
+            .unwrap()
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Arc::new(AttrCell::new(Box::<<T as Attr>::Store>::default())))
+            .clone()
+    }
+
+    /// Returns a mutable reference to the store for an attribute.
+    pub fn get_mut<T: Attr>(&mut self) -> Option<&mut T::Store> {
+        self.stores
+            .get_mut()
+            .unwrap()
+            .get_mut(&TypeId::of::<T>())
+            .map(|cell| {
+                Arc::get_mut(cell)
+                    .expect("attribute store borrowed")
+                    .get_mut()
+                    .downcast_mut()
+                    .unwrap()
+            })
+    }
+
+    /// Removes an attribute store from the group and returns it.
+    pub fn take<T: Attr>(&mut self) -> Option<T::Store> {
+        self.stores
+            .get_mut()
+            .unwrap()
+            .remove(&TypeId::of::<T>())
+            .map(|cell| {
+                let cell =
+                    Arc::try_unwrap(cell).unwrap_or_else(|_| panic!("attribute store borrowed"));
+                *cell.into_inner().downcast().ok().unwrap()
+            })
+    }
+
+    /// Inserts an attribute store into the group.
+    /// Returns the old store for that attribute type, or `None` if there was none.
+    pub fn insert<T: Attr>(&mut self, store: T::Store) -> Option<T::Store> {
+        self.stores
+            .get_mut()
+            .unwrap()
+            .insert(TypeId::of::<T>(), Arc::new(AttrCell::new(Box::new(store))))
+            .map(|cell| {
+                let cell =
+                    Arc::try_unwrap(cell).unwrap_or_else(|_| panic!("attribute store borrowed"));
+                *cell.into_inner().downcast().ok().unwrap()
+            })
+    }
+
+    /// Registers an attribute type in this group.
+    /// If the store for the attribute does not already exist,
+    /// an empty store for the attribute will be created.
+    pub fn register<T: Attr>(&mut self) -> &mut T::Store {
+        let cell = self
+            .stores
+            .get_mut()
+            .unwrap()
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Arc::new(AttrCell::new(Box::<<T as Attr>::Store>::default())));
+        Arc::get_mut(cell)
+            .expect("attribute store borrowed")
+            .get_mut()
+            .downcast_mut()
+            .unwrap()
+    }
+
+    /// Removes a node from all attribute stores in the group.
+    pub fn remove_node(&mut self, node: Node) {
+        for cell in self.stores.get_mut().unwrap().values_mut() {
+            Arc::get_mut(cell)
+                .expect("attribute store borrowed")
+                .get_mut()
+                .remove(node);
+        }
+    }
+
+    /// Rebuilds an [`AttrGroup`] from the name -> JSON map produced by this
+    /// type's [`Serialize`] impl, looking up each name in `registry` to
+    /// recover its concrete store type (the serialized form alone carries no
+    /// type information to do this with).
+    ///
+    /// `on_unknown` controls what happens when a serialized name isn't
+    /// registered in `registry` - e.g. because the HUGR was produced by a
+    /// newer tool version that added an attribute this program doesn't know
+    /// about.
+    pub fn deserialize_with(
+        registry: &AttrRegistry,
+        value: serde_json::Value,
+        on_unknown: UnknownAttr,
+    ) -> Result<Self, AttrDeserializeError> {
+        let serde_json::Value::Object(map) = value else {
+            return Err(AttrDeserializeError::NotAnObject);
+        };
+
+        let mut stores = HashMap::new();
+        for (name, value) in map {
+            let Some(&(type_id, ctor)) = registry.constructors.get(&name) else {
+                match on_unknown {
+                    UnknownAttr::Skip => continue,
+                    UnknownAttr::Error => return Err(AttrDeserializeError::UnknownAttr(name)),
+                }
+            };
+            let store = ctor(value).map_err(|source| AttrDeserializeError::Json {
+                name: name.clone(),
+                source,
+            })?;
+            stores.insert(type_id, Arc::new(AttrCell::new(store)));
+        }
+
+        Ok(Self {
+            stores: RwLock::new(stores),
+        })
+    }
+}
+
+impl Clone for AttrGroup {
+    fn clone(&self) -> Self {
+        let stores = self
+            .stores
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(id, cell)| {
+                let store_ref = try_borrow_cell(cell).expect("attribute store mutably borrowed");
+                (*id, Arc::new(AttrCell::new(store_ref.clone_to_box())))
+            })
+            .collect();
+        Self {
+            stores: RwLock::new(stores),
+        }
+    }
+}
+
+impl Serialize for AttrGroup {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeMap;
+        let stores = self.stores.read().unwrap();
+        let mut map = serializer.serialize_map(Some(stores.len()))?;
+
+        for cell in stores.values() {
+            let store_ref = try_borrow_cell(cell).expect("attribute store mutably borrowed");
+            map.serialize_entry(store_ref.name(), &store_ref.to_json())?;
+        }
+
+        map.end()
+    }
+}
+
+impl Debug for AttrGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut map = f.debug_map();
+        let stores = self.stores.read().unwrap();
+
+        for cell in stores.values() {
+            let store_ref = try_borrow_cell(cell).expect("attribute store mutably borrowed");
+            map.entry(&store_ref.name(), &*store_ref);
+        }
+
+        map.finish()
+    }
+}
+
+/// Maps an attribute's [`Attr::name`] to a constructor that rebuilds its
+/// boxed, type-erased store from JSON - the counterpart
+/// [`AttrGroup::deserialize_with`] needs to undo the name -> JSON map
+/// produced by [`AttrGroup`]'s [`Serialize`] impl, since that map alone
+/// carries no information about which concrete store type each name maps to.
+#[derive(Default, Clone)]
+pub struct AttrRegistry {
+    constructors: HashMap<String, (TypeId, AttrConstructor)>,
+}
+
+type AttrConstructor = fn(serde_json::Value) -> serde_json::Result<Box<dyn AttrStoreDyn>>;
+
+impl AttrRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `T`, so a serialized store named [`T::name()`](Attr::name)
+    /// can be rebuilt by [`AttrGroup::deserialize_with`].
+    pub fn register<T: Attr>(&mut self) -> &mut Self {
+        let ctor: AttrConstructor = |value| {
+            let store = <T::Store as AttrStore>::from_json(value)?;
+            Ok(Box::new(store) as Box<dyn AttrStoreDyn>)
+        };
+        self.constructors
+            .insert(T::name().to_owned(), (TypeId::of::<T>(), ctor));
+        self
+    }
+}
+
+/// What [`AttrGroup::deserialize_with`] does when it encounters a serialized
+/// attribute name that isn't registered in the [`AttrRegistry`] it was given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownAttr {
+    /// Silently drop the attribute, keeping the rest of the group intact.
+    Skip,
+    /// Fail with [`AttrDeserializeError::UnknownAttr`].
+    Error,
+}
+
+/// Errors that can occur while reconstructing an [`AttrGroup`] from JSON via
+/// [`AttrGroup::deserialize_with`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum AttrDeserializeError {
+    /// A serialized attribute's name isn't registered in the given
+    /// [`AttrRegistry`], and [`UnknownAttr::Error`] was requested.
+    #[error("unknown attribute type {0:?}")]
+    UnknownAttr(String),
+    /// A serialized attribute's name was recognised, but its value didn't
+    /// parse as that attribute's store.
+    #[error("failed to deserialize attribute {name:?}: {source}")]
+    Json {
+        /// The attribute whose value failed to parse.
+        name: String,
+        /// The underlying parse error.
+        #[source]
+        source: serde_json::Error,
+    },
+    /// The top-level serialized value wasn't a JSON object.
+    #[error("expected a JSON object mapping attribute name to value")]
+    NotAnObject,
+}
+
+/// Immutable borrow of an attribute store.
+///
+/// As long as this borrow is alive, the attribute can not be mutably borrowed.
+pub struct AttrRef<T: Attr>(
+    AttrCellRef<Box<dyn AttrStoreDyn>>,
+    std::marker::PhantomData<T>,
+);
+
+impl<T: Attr> Deref for AttrRef<T> {
+    type Target = T::Store;
+
+    fn deref(&self) -> &Self::Target {
+        (*self.0).downcast_ref().unwrap()
+    }
+}
+
+/// Mutable borrow of an attribute store.
+///
+/// As long as this borrow is alive, it provides exclusive access to the attribute.
+/// Any attempt to borrow the attribute again (mutably or immutably) before
+/// this reference is dropped will instead get `None` from
+/// [`AttrGroup::try_borrow`]/[`AttrGroup::try_borrow_mut`].
+pub struct AttrRefMut<T: Attr>(
+    AttrCellRefMut<Box<dyn AttrStoreDyn>>,
+    std::marker::PhantomData<T>,
+);
+
+impl<T: Attr> Deref for AttrRefMut<T> {
+    type Target = T::Store;
+
+    fn deref(&self) -> &Self::Target {
+        (*self.0).downcast_ref().unwrap()
+    }
+}
+
+impl<T: Attr> DerefMut for AttrRefMut<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        (*self.0).downcast_mut().unwrap()
+    }
+}
+
+/// Attribute data that can be attached to nodes in a hugr.
+pub trait Attr: 'static + Debug + Clone {
+    /// Type of the [`AttrStore`] which holds attributes of this type.
+    type Store: AttrStore<Attr = Self> + Send + Sync;
+
+    /// Name of the attribute.
+    ///
+    /// This name is implicitly assumed to be unique among all attribute
+    /// types that are used together.
+    fn name() -> &'static str;
+}
+
+/// Internal trait that is used to type erase [`AttrStore`]s
+/// so that they can be stored within an [`AttrGroup`].
+/// The methods in this trait allow the [`AttrGroup`] to perform
+/// operations on the store without knowing the type of the attribute.
+trait AttrStoreDyn: Any + Debug + Send + Sync + 'static {
+    /// Clones the attribute store and returns a trait object for the clone.
+    /// This is necessary since the `Clone` trait itself is not object safe.
+    fn clone_to_box(&self) -> Box<dyn AttrStoreDyn>;
+    /// See [`AttrStore::remove`].
+    fn remove(&mut self, node: Node);
+    /// See [`AttrStore::to_json`].
+    fn to_json(&self) -> serde_json::Value;
+    /// See [`AttrStore::name`].
+    fn name(&self) -> &'static str;
+    /// Upcast to [`Any`] so [`dyn AttrStoreDyn`] can be downcast back to its
+    /// concrete store type.
+    fn as_any(&self) -> &dyn Any;
+    /// See [`Self::as_any`].
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    /// See [`Self::as_any`].
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl Clone for Box<dyn AttrStoreDyn> {
+    fn clone(&self) -> Self {
+        self.clone_to_box()
+    }
+}
+
+impl dyn AttrStoreDyn {
+    /// Downcasts `&self` to its concrete store type, or `None` if `T` isn't
+    /// the store that was actually boxed here.
+    fn downcast_ref<T: AttrStoreDyn>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+
+    /// Downcasts `&mut self` to its concrete store type, or `None` if `T`
+    /// isn't the store that was actually boxed here.
+    fn downcast_mut<T: AttrStoreDyn>(&mut self) -> Option<&mut T> {
+        self.as_any_mut().downcast_mut()
+    }
+
+    /// Downcasts `Box<Self>` to `Box<T>`, or returns the original box if `T`
+    /// isn't the store that was actually boxed here.
+    fn downcast<T: AttrStoreDyn>(self: Box<Self>) -> Result<Box<T>, Box<Self>> {
+        if self.as_any().is::<T>() {
+            Ok(self.into_any().downcast().unwrap())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl<T> AttrStoreDyn for T
+where
+    T: AttrStore + Send + Sync + 'static,
+{
+    fn clone_to_box(&self) -> Box<dyn AttrStoreDyn> {
+        Box::new(self.clone())
+    }
+
+    fn remove(&mut self, node: Node) {
+        <T as AttrStore>::remove(self, node);
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        <T as AttrStore>::to_json(self)
+    }
+
+    fn name(&self) -> &'static str {
+        T::Attr::name()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+/// Storage container for attributes.
+pub trait AttrStore: Debug + Clone + Default {
+    /// The type of attribute in this store.
+    type Attr: Attr<Store = Self>;
+
+    /// Removes the attribute for a node.
+    /// Returns the value of the attribute if it existed before.
+    fn remove(&mut self, node: Node) -> Option<Self::Attr>;
+
+    /// Inserts an attribute for a node.
+    /// Returns the previous value of the attribute if it already existed.
+    fn insert(&mut self, node: Node, attr: Self::Attr) -> Option<Self::Attr>;
+
+    /// Returns an immutable reference to the value of an attribute for a node.
+    fn get(&self, node: Node) -> Option<&Self::Attr>;
+
+    /// Returns a mutable reference to the value of an attribute for a node.
+    fn get_mut(&mut self, node: Node) -> Option<&mut Self::Attr>;
+
+    /// Converts the attribute store to a JSON value.
+    fn to_json(&self) -> serde_json::Value;
+
+    /// Reconstructs a store from the JSON value produced by [`Self::to_json`].
+    fn from_json(value: serde_json::Value) -> serde_json::Result<Self>
+    where
+        Self: Sized;
+
+    // TODO: Iterators
+}
+
+/// Attribute store that sparsely stores the attributes in a hashmap.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Sparse<T> {
+    data: HashMap<Node, T>,
+}
+
+impl<T> Sparse<T>
+where
+    T: Attr<Store = Self>,
+{
+    /// Creates an empty [`Sparse`].
+    pub fn new() -> Self {
+        Self {
+            data: HashMap::new(),
+        }
+    }
+}
+
+impl<T> Default for Sparse<T>
+where
+    T: Attr<Store = Self>,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> AttrStore for Sparse<T>
+where
+    T: Attr<Store = Self> + Serialize + for<'de> Deserialize<'de>,
+{
+    type Attr = T;
+
+    #[inline]
+    fn remove(&mut self, node: Node) -> Option<Self::Attr> {
+        self.data.remove(&node)
+    }
+
+    #[inline]
+    fn insert(&mut self, node: Node, attr: Self::Attr) -> Option<Self::Attr> {
+        self.data.insert(node, attr)
+    }
+
+    #[inline]
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap()
+    }
+
+    #[inline]
+    fn from_json(value: serde_json::Value) -> serde_json::Result<Self> {
+        serde_json::from_value(value)
+    }
+
+    #[inline]
+    fn get(&self, node: Node) -> Option<&Self::Attr> {
+        self.data.get(&node)
+    }
+
+    #[inline]
+    fn get_mut(&mut self, node: Node) -> Option<&mut Self::Attr> {
+        self.data.get_mut(&node)
+    }
+}
+
+impl<T> Index<Node> for Sparse<T>
+where
+    T: Attr<Store = Self>,
+{
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: Node) -> &Self::Output {
+        &self.data[&index]
+    }
+}
+
+impl<T> IndexMut<Node> for Sparse<T>
+where
+    T: Attr<Store = Self>,
+{
+    #[inline]
+    fn index_mut(&mut self, index: Node) -> &mut Self::Output {
+        self.data.get_mut(&index).unwrap()
+    }
+}
+
+/// Implement [`Attr`] for an attribute with [`Sparse`] store.
+#[macro_export]
+macro_rules! impl_attr_sparse {
+    ($type:ty, $name:expr) => {
+        impl $crate::hugr::attributes::Attr for $type {
+            type Store = $crate::hugr::attributes::Sparse<$type>;
+
+            #[inline]
+            fn name() -> &'static str {
+                $name
+            }
+        }
+    };
+}
+
+pub use impl_attr_sparse;
+
+/// Attribute store that densely stores attributes in a contiguous
+/// `Vec<Option<T>>`, indexed by the node's underlying integer id, alongside a
+/// running count of the occupied slots.
+///
+/// Prefer this over [`Sparse`] when an attribute is set on nearly every node
+/// (e.g. a per-node type or layout annotation): a lookup is a single indexed
+/// load rather than a hash, and iterating the present entries stays
+/// cache-local. [`Sparse`]'s hashmap remains the better choice for an
+/// attribute only a few nodes carry, since `Dense`'s `Vec` is sized by the
+/// largest node id seen so far, regardless of how many slots are actually
+/// set.
+#[derive(Debug, Clone)]
+pub struct Dense<T> {
+    data: Vec<Option<T>>,
+    len: usize,
+}
+
+impl<T> Dense<T>
+where
+    T: Attr<Store = Self>,
+{
+    /// Creates an empty [`Dense`].
+    pub fn new() -> Self {
+        Self {
+            data: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<T> Default for Dense<T>
+where
+    T: Attr<Store = Self>,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> AttrStore for Dense<T>
+where
+    T: Attr<Store = Self> + Serialize + for<'de> Deserialize<'de>,
+{
+    type Attr = T;
+
+    #[inline]
+    fn remove(&mut self, node: Node) -> Option<Self::Attr> {
+        let slot = self.data.get_mut(node.index())?.take();
+        if slot.is_some() {
+            self.len -= 1;
+        }
+        slot
+    }
+
+    #[inline]
+    fn insert(&mut self, node: Node, attr: Self::Attr) -> Option<Self::Attr> {
+        let index = node.index();
+        if index >= self.data.len() {
+            self.data.resize_with(index + 1, || None);
+        }
+        let prev = self.data[index].replace(attr);
+        if prev.is_none() {
+            self.len += 1;
+        }
+        prev
+    }
+
+    #[inline]
+    fn get(&self, node: Node) -> Option<&Self::Attr> {
+        self.data.get(node.index())?.as_ref()
+    }
+
+    #[inline]
+    fn get_mut(&mut self, node: Node) -> Option<&mut Self::Attr> {
+        self.data.get_mut(node.index())?.as_mut()
+    }
+
+    /// Serializes as a sparse node-index -> value map, same as [`Sparse`],
+    /// so the two stores stay interchangeable on the wire: which one a
+    /// program picks is a local performance choice, not something that
+    /// should leak into the serialized format.
+    fn to_json(&self) -> serde_json::Value {
+        let mut map = serde_json::Map::with_capacity(self.len);
+        for (index, slot) in self.data.iter().enumerate() {
+            if let Some(value) = slot {
+                map.insert(index.to_string(), serde_json::to_value(value).unwrap());
+            }
+        }
+        serde_json::Value::Object(map)
+    }
+
+    fn from_json(value: serde_json::Value) -> serde_json::Result<Self> {
+        let serde_json::Value::Object(map) = value else {
+            return Err(serde_json::Error::custom(
+                "expected a JSON object mapping node index to value",
+            ));
+        };
+
+        let mut data = Vec::new();
+        let mut len = 0;
+        for (key, value) in map {
+            let index: usize = key.parse().map_err(serde_json::Error::custom)?;
+            if index >= data.len() {
+                data.resize_with(index + 1, || None);
+            }
+            data[index] = Some(serde_json::from_value(value)?);
+            len += 1;
+        }
+
+        Ok(Self { data, len })
+    }
+}
+
+impl<T> Index<Node> for Dense<T>
+where
+    T: Attr<Store = Self>,
+{
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: Node) -> &Self::Output {
+        self.data[index.index()].as_ref().unwrap()
+    }
+}
+
+impl<T> IndexMut<Node> for Dense<T>
+where
+    T: Attr<Store = Self>,
+{
+    #[inline]
+    fn index_mut(&mut self, index: Node) -> &mut Self::Output {
+        self.data[index.index()].as_mut().unwrap()
+    }
+}
+
+/// Implement [`Attr`] for an attribute with [`Dense`] store.
+#[macro_export]
+macro_rules! impl_attr_dense {
+    ($type:ty, $name:expr) => {
+        impl $crate::hugr::attributes::Attr for $type {
+            type Store = $crate::hugr::attributes::Dense<$type>;
+
+            #[inline]
+            fn name() -> &'static str {
+                $name
+            }
+        }
+    };
+}
+
+pub use impl_attr_dense;
+
+/// Marker type requesting a shared borrow of `T`'s store from
+/// [`AttrGroup::fetch`].
+pub struct Read<T>(std::marker::PhantomData<T>);
+
+/// Marker type requesting an exclusive borrow of `T`'s store from
+/// [`AttrGroup::fetch`].
+pub struct Write<T>(std::marker::PhantomData<T>);
+
+/// A request for one or more attribute stores to borrow from an
+/// [`AttrGroup`] in a single, alias-checked call. Implemented for [`Read`]
+/// and [`Write`] themselves, and for tuples of them up to arity 8 (see
+/// [`impl_attr_fetch_tuple`]) so a caller can declare its whole read/write
+/// set at once, e.g. `group.fetch::<(Read<Foo>, Write<Bar>)>()`.
+pub trait AttrFetch {
+    /// The borrow guard(s) this fetch produces.
+    type Output;
+
+    /// The [`TypeId`]s this fetch will borrow, in the order its guards are
+    /// returned. [`AttrGroup::fetch`] checks these are pairwise distinct
+    /// before calling [`Self::fetch`].
+    fn type_ids() -> Vec<TypeId>;
+
+    /// Acquires the borrow(s) this fetch declared via [`Self::type_ids`].
+    fn fetch(group: &AttrGroup) -> Self::Output;
+}
+
+impl<T: Attr> AttrFetch for Read<T> {
+    type Output = AttrRef<T>;
+
+    fn type_ids() -> Vec<TypeId> {
+        vec![TypeId::of::<T>()]
+    }
+
+    fn fetch(group: &AttrGroup) -> Self::Output {
+        group.borrow::<T>()
+    }
+}
+
+impl<T: Attr> AttrFetch for Write<T> {
+    type Output = AttrRefMut<T>;
+
+    fn type_ids() -> Vec<TypeId> {
+        vec![TypeId::of::<T>()]
+    }
+
+    fn fetch(group: &AttrGroup) -> Self::Output {
+        group.borrow_mut::<T>()
+    }
+}
+
+/// Implements [`AttrFetch`] for a tuple of types which are themselves
+/// [`AttrFetch`] (i.e. [`Read`]/[`Write`] markers, or smaller such tuples).
+macro_rules! impl_attr_fetch_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: AttrFetch),+> AttrFetch for ($($name,)+) {
+            type Output = ($($name::Output,)+);
+
+            fn type_ids() -> Vec<TypeId> {
+                let mut ids = Vec::new();
+                $(ids.extend($name::type_ids());)+
+                ids
+            }
+
+            fn fetch(group: &AttrGroup) -> Self::Output {
+                ($($name::fetch(group),)+)
+            }
+        }
+    };
+}
+
+impl_attr_fetch_tuple!(A);
+impl_attr_fetch_tuple!(A, B);
+impl_attr_fetch_tuple!(A, B, C);
+impl_attr_fetch_tuple!(A, B, C, D);
+impl_attr_fetch_tuple!(A, B, C, D, E);
+impl_attr_fetch_tuple!(A, B, C, D, E, F);
+impl_attr_fetch_tuple!(A, B, C, D, E, F, G);
+impl_attr_fetch_tuple!(A, B, C, D, E, F, G, H);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hugr::Hugr;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Weight(u32);
+    impl_attr_sparse!(Weight, "test.weight");
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct DenseWeight(u32);
+    impl_attr_dense!(DenseWeight, "test.dense_weight");
+
+    #[test]
+    fn live_read_blocks_write() {
+        let group = AttrGroup::new();
+        group.with_mut::<Weight, _>(|s| {
+            s.insert(Hugr::new().root(), Weight(1));
+        });
+
+        let _read = group.borrow::<Weight>();
+        assert!(group.try_borrow_mut::<Weight>().is_none());
+    }
+
+    #[test]
+    fn live_write_blocks_read() {
+        let group = AttrGroup::new();
+        group.with_mut::<Weight, _>(|_| {});
+
+        let _write = group.borrow_mut::<Weight>();
+        assert!(group.try_borrow::<Weight>().is_none());
+    }
+
+    #[test]
+    fn borrow_is_reacquirable_once_released() {
+        let group = AttrGroup::new();
+        group.with_mut::<Weight, _>(|_| {});
+
+        {
+            let _write = group.borrow_mut::<Weight>();
+            assert!(group.try_borrow::<Weight>().is_none());
+        }
+        // The exclusive borrow above has been dropped, so both a shared and
+        // (once that one's dropped too) an exclusive borrow succeed again.
+        let read = group.try_borrow::<Weight>();
+        assert!(read.is_some());
+        drop(read);
+        assert!(group.try_borrow_mut::<Weight>().is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "conflicting attribute access")]
+    fn fetch_rejects_the_same_attribute_twice() {
+        let mut group = AttrGroup::new();
+        group.register::<Weight>();
+
+        let _ = group.fetch::<(Read<Weight>, Write<Weight>)>();
+    }
+
+    #[test]
+    fn sparse_and_dense_stores_serialize_to_the_same_json() {
+        let hugr = Hugr::new();
+        let node = hugr.root();
+
+        let mut sparse: Sparse<Weight> = Sparse::new();
+        sparse.insert(node, Weight(42));
+
+        let mut dense: Dense<DenseWeight> = Dense::new();
+        dense.insert(node, DenseWeight(42));
+
+        // Same node-index -> value shape regardless of which store produced
+        // it, and each can be read back through the *other* store's
+        // `from_json`, as `Dense::to_json`'s docs promise they're interchangeable.
+        assert_eq!(sparse.to_json(), dense.to_json());
+
+        let via_dense = Dense::<DenseWeight>::from_json(sparse.to_json()).unwrap();
+        assert_eq!(via_dense.get(node), Some(&DenseWeight(42)));
+
+        let via_sparse = Sparse::<Weight>::from_json(dense.to_json()).unwrap();
+        assert_eq!(via_sparse.get(node), Some(&Weight(42)));
+    }
+}
@@ -0,0 +1,177 @@
+//! Monomorphization of polymorphic function calls.
+//!
+//! A full pass would walk a [`Hugr`](super::Hugr), seed a worklist from every
+//! call site whose target `FuncDefn` carries type parameters, clone and
+//! specialize the callee's subtree for each distinct argument list (reusing
+//! a memoized copy for repeat calls, so recursive/nested generic calls
+//! terminate), redirect the call edge to the specialized copy, and finally
+//! drop any `FuncDefn` left with no remaining callers. That graph-rewriting
+//! half needs a call-site op to seed the worklist from and a way to clone a
+//! node's subtree and redirect an edge onto the copy - none of which exist
+//! on [`Hugr`](super::Hugr) yet (there is no `LoadFunction`/`Call` op, and no
+//! mutation API beyond [`Hugr::add_node`](super::Hugr::add_node) /
+//! [`Hugr::connect`](super::Hugr::connect) / [`Hugr::set_parent`](super::Hugr::set_parent)).
+//!
+//! What *is* implementable against what exists today is the other half: for
+//! a given `(FuncDefn, concrete TypeArg list)` pair, produce the specialized,
+//! fully-monomorphic [`FunctionType`] that the copy's signature would carry,
+//! and memoize it so that two calls with the same arguments share one
+//! specialization. [`Monomorphizer`] is that memo; once call-site ops and a
+//! subtree-clone/redirect API land on [`Hugr`], the worklist loop described
+//! above can drive it per descendant node instead of just the top-level
+//! signature.
+//!
+//! To be explicit about scope: [`Monomorphizer`] is *not* the monomorphization
+//! pass this module is named after. It has no worklist, touches no edges,
+//! clones no subtree, and removes no `FuncDefn` - it is the one piece of that
+//! pass (computing and memoizing a specialized signature) that doesn't
+//! depend on the missing graph-rewriting APIs above. Treat the rest of the
+//! pass as blocked on those APIs landing, not as something this memo stands
+//! in for.
+use portgraph::NodeIndex;
+
+use crate::extension::{ExtensionRegistry, SignatureError};
+use crate::types::type_param::TypeArg;
+use crate::types::{FunctionType, PolyFuncType};
+
+/// Memoizes the [`FunctionType`] produced by instantiating a polymorphic
+/// `FuncDefn` with a concrete argument list, keyed on the defining node and
+/// the arguments, so that repeat calls - including the recursive/nested
+/// generic calls a full worklist-driven pass would otherwise re-specialize
+/// on every visit - resolve to the same specialization.
+///
+/// Looked up by linear scan rather than a `HashMap`: [`TypeArg`] supports
+/// equality but not hashing, and the number of distinct specializations of
+/// any one `FuncDefn` is expected to stay small.
+#[derive(Default)]
+pub struct Monomorphizer {
+    memo: Vec<(NodeIndex, Vec<TypeArg>, FunctionType)>,
+}
+
+impl Monomorphizer {
+    /// Creates an empty memo.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the specialized signature for calling the `FuncDefn` at
+    /// `node` (whose declared polymorphic signature is `poly_fn`) with
+    /// `args`, instantiating and memoizing it on first use.
+    ///
+    /// `args` must fully instantiate `poly_fn` - partial application (fewer
+    /// `args` than `poly_fn` declares parameters) is rejected, matching the
+    /// assumption that every call site seeding the worklist supplies a
+    /// concrete argument for each parameter.
+    pub fn specialize(
+        &mut self,
+        node: NodeIndex,
+        poly_fn: &PolyFuncType,
+        args: &[TypeArg],
+        extension_registry: &ExtensionRegistry,
+    ) -> Result<&FunctionType, SignatureError> {
+        let already_memoized = self
+            .memo
+            .iter()
+            .position(|(n, a, _)| *n == node && a == args);
+        let idx = match already_memoized {
+            Some(idx) => idx,
+            None => {
+                let instantiated = poly_fn.instantiate_all(args, extension_registry)?;
+                self.memo.push((node, args.to_vec(), instantiated));
+                self.memo.len() - 1
+            }
+        };
+        Ok(&self.memo[idx].2)
+    }
+
+    /// The number of distinct `(FuncDefn, args)` specializations memoized so
+    /// far.
+    pub fn len(&self) -> usize {
+        self.memo.len()
+    }
+
+    /// Whether any specialization has been memoized yet.
+    pub fn is_empty(&self) -> bool {
+        self.memo.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use portgraph::NodeIndex;
+
+    use crate::extension::prelude::{PRELUDE_REGISTRY, USIZE_T};
+    use crate::types::type_param::{TypeArg, TypeParam};
+    use crate::types::{FunctionType, PolyFuncType, Type, TypeBound};
+
+    use super::Monomorphizer;
+
+    fn identity_fn() -> PolyFuncType {
+        let var = Type::new_var_use(0, TypeBound::Any);
+        PolyFuncType::new_validated(
+            [TypeParam::Type(TypeBound::Any)],
+            FunctionType::new(vec![var.clone()], vec![var]),
+            &PRELUDE_REGISTRY,
+        )
+        .unwrap()
+    }
+
+    fn usize_arg() -> Vec<TypeArg> {
+        vec![TypeArg::Type { ty: USIZE_T }]
+    }
+
+    #[test]
+    fn specializing_twice_with_the_same_args_reuses_the_memo() {
+        let node = NodeIndex::new(0);
+        let poly_fn = identity_fn();
+        let mut mono = Monomorphizer::new();
+
+        let first = mono
+            .specialize(node, &poly_fn, &usize_arg(), &PRELUDE_REGISTRY)
+            .unwrap()
+            .clone();
+        assert_eq!(mono.len(), 1);
+
+        let second = mono
+            .specialize(node, &poly_fn, &usize_arg(), &PRELUDE_REGISTRY)
+            .unwrap()
+            .clone();
+        assert_eq!(first, second);
+        // No new entry was memoized for the repeat call.
+        assert_eq!(mono.len(), 1);
+    }
+
+    #[test]
+    fn different_nodes_or_args_get_distinct_entries() {
+        let poly_fn = identity_fn();
+        let mut mono = Monomorphizer::new();
+        assert!(mono.is_empty());
+
+        mono.specialize(NodeIndex::new(0), &poly_fn, &usize_arg(), &PRELUDE_REGISTRY)
+            .unwrap();
+        // Same args, different defining node.
+        mono.specialize(NodeIndex::new(1), &poly_fn, &usize_arg(), &PRELUDE_REGISTRY)
+            .unwrap();
+        // Same node, different args - a second, distinct instantiation of
+        // the same recursive/generic `FuncDefn`, which is exactly the case
+        // a worklist-driven pass needs memoized to terminate.
+        let bool_arg = vec![TypeArg::Type {
+            ty: crate::extension::prelude::BOOL_T,
+        }];
+        mono.specialize(NodeIndex::new(0), &poly_fn, &bool_arg, &PRELUDE_REGISTRY)
+            .unwrap();
+
+        assert_eq!(mono.len(), 3);
+    }
+
+    #[test]
+    fn specialize_propagates_an_instantiation_error() {
+        let node = NodeIndex::new(0);
+        let poly_fn = identity_fn();
+        let mut mono = Monomorphizer::new();
+
+        // Too few args for the one declared parameter.
+        assert!(mono.specialize(node, &poly_fn, &[], &PRELUDE_REGISTRY).is_err());
+        assert!(mono.is_empty());
+    }
+}
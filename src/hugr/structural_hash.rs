@@ -0,0 +1,223 @@
+//! Alpha-invariant structural hashing of [`Hugr`] subtrees.
+//!
+//! A [`Node`](portgraph::NodeIndex) index is an implementation detail of how
+//! a particular [`Hugr`] happens to be laid out in memory - two subtrees
+//! that are identical in every way a consumer can observe (same ops, same
+//! edges, same children, recursively) can still be numbered completely
+//! differently. [`Hugr::structural_hash`] folds a rooted subtree down to a
+//! `u64` that ignores that numbering, in the same spirit as Dhall deriving a
+//! normal-form hash that's independent of bound-variable names: any two
+//! subtrees a consumer should treat as "the same function" hash the same,
+//! and (building on [`PolyFuncType`](crate::types::PolyFuncType)'s already
+//! position-based, not name-based, type variables) nothing about the hash
+//! depends on anything but structure.
+//!
+//! Because a hash collision would silently merge two different subtrees,
+//! [`Hugr::structural_eq`] is provided alongside it: callers doing dedup
+//! should only ever act on equal hashes *and* a passing [`Hugr::structural_eq`]
+//! check, never the hash alone.
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use portgraph::{Direction, NodeIndex};
+
+use super::Hugr;
+use crate::ops::OpType;
+
+impl Hugr {
+    /// Computes an alpha-invariant structural hash of the subtree rooted at
+    /// `root`: two subtrees (in this [`Hugr`] or different ones) that are
+    /// indistinguishable except for how their nodes happen to be numbered
+    /// hash to the same value.
+    ///
+    /// A collision is possible (this folds an unbounded subtree into 64
+    /// bits) - [`Self::structural_eq`] makes the no-false-positives
+    /// guarantee dedup needs.
+    pub fn structural_hash(&self, root: NodeIndex) -> u64 {
+        let index = self.local_indices(root);
+        let mut hasher = DefaultHasher::new();
+        self.hash_node(root, &index, &mut hasher);
+        hasher.finish()
+    }
+
+    /// Whether the subtree rooted at `root` is structurally identical to the
+    /// subtree rooted at `other_root` in `other` - same op, same edges (an
+    /// edge leaving the subtree counts as equal iff both leave at the same
+    /// port and arrive at the same port offset, regardless of which outside
+    /// node that port belongs to), and the same recursively-equal
+    /// children in the same order.
+    pub fn structural_eq(&self, root: NodeIndex, other: &Hugr, other_root: NodeIndex) -> bool {
+        let index = self.local_indices(root);
+        let other_index = other.local_indices(other_root);
+        self.nodes_eq(root, &index, other, other_root, &other_index)
+    }
+
+    /// Assigns every node in the subtree rooted at `root` a position-stable
+    /// local index: a preorder walk of the hierarchy, so two isomorphic
+    /// subtrees assign the same local index to corresponding nodes
+    /// regardless of their actual [`NodeIndex`]es.
+    fn local_indices(&self, root: NodeIndex) -> HashMap<NodeIndex, usize> {
+        let mut index = HashMap::new();
+        self.assign_local_indices(root, &mut index);
+        index
+    }
+
+    fn assign_local_indices(&self, node: NodeIndex, index: &mut HashMap<NodeIndex, usize>) {
+        let next = index.len();
+        index.insert(node, next);
+        for child in self.hierarchy.children(node) {
+            self.assign_local_indices(child, index);
+        }
+    }
+
+    fn hash_node(
+        &self,
+        node: NodeIndex,
+        index: &HashMap<NodeIndex, usize>,
+        hasher: &mut impl Hasher,
+    ) {
+        self.op_types[node].to_cbor_bytes().hash(hasher);
+        self.hash_ports(node, index, hasher);
+        for child in self.hierarchy.children(node) {
+            self.hash_node(child, index, hasher);
+        }
+    }
+
+    fn hash_ports(
+        &self,
+        node: NodeIndex,
+        index: &HashMap<NodeIndex, usize>,
+        hasher: &mut impl Hasher,
+    ) {
+        for dir in [Direction::Incoming, Direction::Outgoing] {
+            (dir == Direction::Outgoing).hash(hasher);
+            for offset in self.graph.port_offsets(node, dir) {
+                offset.index().hash(hasher);
+                let port = self
+                    .graph
+                    .port_index(node, offset)
+                    .expect("offset came from this node");
+                for (_, link) in self.graph.port_links(port) {
+                    let other_port = link.port();
+                    let other_node = self
+                        .graph
+                        .port_node(other_port)
+                        .expect("linked ports belong to a node");
+                    let other_offset = self
+                        .graph
+                        .port_offset(other_port)
+                        .expect("linked ports have an offset");
+                    match index.get(&other_node) {
+                        // The other end is inside this subtree: name it by its
+                        // local, renumbering-stable index.
+                        Some(local) => {
+                            0u8.hash(hasher);
+                            local.hash(hasher);
+                        }
+                        // The other end is outside this subtree - all we can say
+                        // without depending on the rest of the Hugr's numbering
+                        // is that the edge leaves, and (below) at which port
+                        // offset it arrives.
+                        None => 1u8.hash(hasher),
+                    }
+                    other_offset.index().hash(hasher);
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn nodes_eq(
+        &self,
+        node: NodeIndex,
+        index: &HashMap<NodeIndex, usize>,
+        other: &Hugr,
+        other_node: NodeIndex,
+        other_index: &HashMap<NodeIndex, usize>,
+    ) -> bool {
+        if self.op_types[node] != other.op_types[other_node] {
+            return false;
+        }
+        if !self.ports_eq(node, index, other, other_node, other_index) {
+            return false;
+        }
+        let children: Vec<_> = self.hierarchy.children(node).collect();
+        let other_children: Vec<_> = other.hierarchy.children(other_node).collect();
+        children.len() == other_children.len()
+            && children
+                .into_iter()
+                .zip(other_children)
+                .all(|(c, oc)| self.nodes_eq(c, index, other, oc, other_index))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn ports_eq(
+        &self,
+        node: NodeIndex,
+        index: &HashMap<NodeIndex, usize>,
+        other: &Hugr,
+        other_node: NodeIndex,
+        other_index: &HashMap<NodeIndex, usize>,
+    ) -> bool {
+        for dir in [Direction::Incoming, Direction::Outgoing] {
+            let offsets: Vec<_> = self.graph.port_offsets(node, dir).collect();
+            let other_offsets: Vec<_> = other.graph.port_offsets(other_node, dir).collect();
+            if offsets.len() != other_offsets.len() {
+                return false;
+            }
+            for (offset, other_offset) in offsets.into_iter().zip(other_offsets) {
+                let links = self.endpoint_fingerprints(node, offset, index);
+                let other_links =
+                    other.endpoint_fingerprints(other_node, other_offset, other_index);
+                if links != other_links {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// The renumbering-stable identity of every link at `node`'s port
+    /// `offset`: `Some(local index)` if the other end is inside the subtree
+    /// `index` was built for, `None` if it leaves the subtree (in which case
+    /// the outside node itself is not compared), each paired with the port
+    /// offset at the other end.
+    fn endpoint_fingerprints(
+        &self,
+        node: NodeIndex,
+        offset: portgraph::PortOffset,
+        index: &HashMap<NodeIndex, usize>,
+    ) -> Vec<(Option<usize>, usize)> {
+        let port = self
+            .graph
+            .port_index(node, offset)
+            .expect("offset came from this node");
+        self.graph
+            .port_links(port)
+            .map(|(_, link)| {
+                let other_port = link.port();
+                let other_node = self
+                    .graph
+                    .port_node(other_port)
+                    .expect("linked ports belong to a node");
+                let other_offset = self
+                    .graph
+                    .port_offset(other_port)
+                    .expect("linked ports have an offset");
+                (index.get(&other_node).copied(), other_offset.index())
+            })
+            .collect()
+    }
+}
+
+impl OpType {
+    /// Canonical CBOR encoding of this op, used as the hash input for
+    /// [`Hugr::structural_hash`] - cheaper to compare/hash than deriving a
+    /// bespoke `Hash` impl for every op variant by hand.
+    fn to_cbor_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        ciborium::into_writer(self, &mut buf).expect("encoding an OpType as CBOR cannot fail");
+        buf
+    }
+}
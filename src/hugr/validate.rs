@@ -0,0 +1,7 @@
+//! Validation helpers built on top of [`Hugr::validate`](crate::hugr::Hugr::validate).
+//!
+//! [`incremental`] reuses the per-node checks `Hugr` itself validates with
+//! to revalidate only the part of the graph an edit may have affected,
+//! instead of repeating the whole-graph pass on every call.
+
+pub mod incremental;
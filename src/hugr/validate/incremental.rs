@@ -0,0 +1,168 @@
+//! Dependency-tracked incremental validation.
+//!
+//! [Hugr::validate] re-checks every node and edge on every call, which is
+//! wasteful for editor/optimizer workflows that revalidate repeatedly
+//! between small edits. [CachedValidator] instead caches the result of
+//! [Hugr::validate_node_ports] + [Hugr::validate_node_edges] +
+//! [Hugr::validate_node_hierarchy] - the three per-node checks
+//! [Hugr::validate] itself is built from - per node, and only recomputes
+//! the nodes named as dirty by [CachedValidator::revalidate] plus their
+//! immediate [Hugr::local_neighbours] (since a node's own edge checks
+//! depend on its neighbours' signatures too). Recomputed work is bounded by
+//! the size of the dirtied region (plus any node not yet cached), not the
+//! whole graph.
+use std::collections::{HashMap, HashSet};
+
+use portgraph::NodeIndex;
+
+use crate::hugr::{Hugr, ValidationError};
+
+/// Caches the per-node [Hugr::validate] result for every node that's been
+/// checked since it (or a neighbour) last changed, so that
+/// [CachedValidator::revalidate] only has to recompute the nodes a caller
+/// names as dirty.
+#[derive(Default)]
+pub struct CachedValidator {
+    /// The last-computed per-node result, keyed by node. A node absent
+    /// from this map needs (re)computing before it can be trusted.
+    cached: HashMap<NodeIndex, Result<(), ValidationError>>,
+    /// The subset of `cached`'s keys whose cached result is an error, so
+    /// [Self::revalidate] doesn't have to scan every cached entry to answer
+    /// "is anything currently invalid".
+    failing: HashSet<NodeIndex>,
+}
+
+impl CachedValidator {
+    /// Creates an empty cache. The first [Self::revalidate] call always
+    /// checks every node, since nothing is cached yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Revalidates `hugr`, recomputing only `dirty` and its immediate
+    /// neighbours (see [Hugr::local_neighbours]) and reusing the cached
+    /// result for everything else. Pass the nodes an edit touched directly
+    /// - there is no need to include their neighbours, [Self::revalidate]
+    /// expands the dirty set itself.
+    pub fn revalidate(
+        &mut self,
+        hugr: &Hugr,
+        dirty: impl IntoIterator<Item = NodeIndex>,
+    ) -> Result<(), ValidationError> {
+        for node in dirty {
+            self.forget(node);
+            for neighbour in hugr.local_neighbours(node).collect::<Vec<_>>() {
+                self.forget(neighbour);
+            }
+        }
+
+        let stale: Vec<NodeIndex> = hugr
+            .graph
+            .nodes_iter()
+            .filter(|node| !self.cached.contains_key(node))
+            .collect();
+        for node in stale {
+            let result = hugr
+                .validate_node_ports(node)
+                .and_then(|()| hugr.validate_node_edges(node))
+                .and_then(|()| hugr.validate_node_hierarchy(node));
+            if result.is_err() {
+                self.failing.insert(node);
+            }
+            self.cached.insert(node, result);
+        }
+
+        match self.failing.iter().next() {
+            Some(node) => self.cached[node].clone(),
+            None => Ok(()),
+        }
+    }
+
+    /// Drops every cached result, forcing the next [Self::revalidate] to
+    /// recheck the whole graph. Useful when a caller can't name the
+    /// affected nodes precisely (e.g. after an [`apply_rewrite`] whose
+    /// extent wasn't tracked).
+    ///
+    /// [`apply_rewrite`]: Hugr::apply_rewrite
+    pub fn invalidate(&mut self) {
+        self.cached.clear();
+        self.failing.clear();
+    }
+
+    /// Drops the cached result for `node`, if any.
+    fn forget(&mut self, node: NodeIndex) {
+        self.cached.remove(&node);
+        self.failing.remove(&node);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::{LeafOp, OpType};
+    use crate::types::Type;
+
+    fn two_node_hugr() -> (Hugr, NodeIndex, NodeIndex) {
+        let mut hugr = Hugr::new();
+        let parent = hugr.root();
+        let ty = Type::new_unit();
+        let a = hugr.add_node(OpType::LeafOp(LeafOp::Noop { ty: ty.clone() }));
+        let b = hugr.add_node(OpType::LeafOp(LeafOp::Noop { ty }));
+        hugr.set_parent(a, parent).unwrap();
+        hugr.set_parent(b, parent).unwrap();
+        (hugr, a, b)
+    }
+
+    #[test]
+    fn reuses_cache_until_a_node_is_named_dirty() {
+        let (hugr, a, _b) = two_node_hugr();
+
+        let mut validator = CachedValidator::new();
+        assert_eq!(validator.revalidate(&hugr, []), Ok(()));
+        assert_eq!(validator.cached.len(), 3); // root + a + b
+
+        // Naming no nodes as dirty reuses every cached entry.
+        assert_eq!(validator.revalidate(&hugr, []), Ok(()));
+        assert_eq!(validator.cached.len(), 3);
+
+        // Naming `a` as dirty only forgets `a` (it has no edges, so no
+        // neighbours get swept in) - everything else stays cached.
+        assert_eq!(validator.revalidate(&hugr, [a]), Ok(()));
+        assert_eq!(validator.cached.len(), 3);
+    }
+
+    #[test]
+    fn dirtying_a_node_also_forgets_its_neighbours() {
+        let (mut hugr, a, b) = two_node_hugr();
+        hugr.connect(a, 0, b, 0).unwrap();
+
+        let mut validator = CachedValidator::new();
+        assert_eq!(validator.revalidate(&hugr, []), Ok(()));
+
+        // `forget` on its own drops only `a`; `b` stays cached.
+        validator.forget(a);
+        assert!(!validator.cached.contains_key(&a));
+        assert!(validator.cached.contains_key(&b));
+
+        // Poison `b`'s cached entry: naming `a` dirty only returns `Ok` if
+        // `revalidate` also forgot and rechecked its neighbour `b`, since
+        // `b`'s edge check depends on `a`'s signature too.
+        validator.cached.insert(b, Err(ValidationError::DanglingNode(b)));
+        validator.failing.insert(b);
+        assert_eq!(validator.revalidate(&hugr, [a]), Ok(()));
+        assert_eq!(validator.cached.len(), 3);
+    }
+
+    #[test]
+    fn invalidate_drops_every_cached_entry() {
+        let (hugr, _a, _b) = two_node_hugr();
+
+        let mut validator = CachedValidator::new();
+        validator.revalidate(&hugr, []).unwrap();
+        assert!(!validator.cached.is_empty());
+
+        validator.invalidate();
+        assert!(validator.cached.is_empty());
+        assert!(validator.failing.is_empty());
+    }
+}
@@ -1,6 +1,11 @@
 //! Read-only access into HUGR graphs and subgraphs.
 
+pub mod adjacency;
+mod cycle;
 pub mod descendants;
+mod insert_lift;
+mod isomorphism;
+mod owned_petgraph;
 pub mod petgraph;
 pub mod sibling;
 pub mod sibling_subgraph;
@@ -9,7 +14,11 @@ pub mod sibling_subgraph;
 mod tests;
 
 pub use self::petgraph::PetgraphWrapper;
+pub use adjacency::{export_adjacency_matrix, parse_adjacency_matrix, AdjacencyMatrix};
+pub use cycle::find_cycle;
 pub use descendants::DescendantsGraph;
+pub use insert_lift::insert_lift_nodes;
+pub use owned_petgraph::OwnedPetgraph;
 use ouroboros::self_referencing;
 pub use sibling::SiblingGraph;
 pub use sibling_subgraph::SiblingSubgraph;
@@ -251,6 +260,36 @@ pub trait HugrView<'m>: sealed::HugrInternals<'m> {
         PetgraphWrapper { hugr: self }
     }
 
+    /// Decides whether this HUGR is isomorphic to `other`, up to node
+    /// renaming, using a VF2-style search over a partial node bijection.
+    ///
+    /// `node_match`/`edge_match` let the caller decide when two nodes or
+    /// edges should be considered equivalent - e.g. comparing [`OpType`]s up
+    /// to metadata, or treating two [`EdgeKind`]s as interchangeable - rather
+    /// than requiring raw index/kind equality.
+    fn is_isomorphic_to<'v, V>(
+        &self,
+        other: &V,
+        mut node_match: impl FnMut(&OpType, &OpType) -> bool,
+        mut edge_match: impl FnMut(EdgeKind, EdgeKind) -> bool,
+    ) -> bool
+    where
+        V: HugrView<'v>,
+        Self: Sized,
+    {
+        isomorphism::is_isomorphic(self, other, &mut node_match, &mut edge_match)
+    }
+
+    /// Materializes this view as an owned [`OwnedPetgraph`], for algorithms
+    /// that need a `StableGraph` rather than a borrowing view - toposort,
+    /// SCC/condensation, Dijkstra, connected components, and so on.
+    fn to_petgraph(&self) -> OwnedPetgraph
+    where
+        Self: Sized,
+    {
+        owned_petgraph::to_petgraph(self)
+    }
+
     /// Return dot string showing underlying graph and hierarchy side by side.
     fn dot_string(&self) -> String {
         let hugr = self.base_hugr();
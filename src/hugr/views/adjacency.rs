@@ -0,0 +1,125 @@
+//! Text adjacency-matrix import/export for a HUGR dataflow region.
+//!
+//! The format is the one used by `petgraph`'s own benchmark harness: an
+//! optional header line naming the operation at each row, followed by one
+//! line per node of space-separated `0`/`1` entries, where row `i` column
+//! `j` means an edge from the `i`th node to the `j`th node. It's a
+//! lightweight, human-editable interchange format for sketching and loading
+//! small graphs in tests and tooling.
+use itertools::Itertools;
+
+use crate::ops::OpName;
+use crate::Node;
+
+use super::HugrView;
+
+/// Serializes the *children* of `root` (i.e. the dataflow region `root`
+/// parents) as a text adjacency matrix, in the order returned by
+/// [`HugrView::children`]. A header line of `OpType` names is always
+/// emitted, since it's needed to round-trip through [`parse_adjacency_matrix`].
+pub fn export_adjacency_matrix<'a>(hugr: &impl HugrView<'a>, root: Node) -> String {
+    let nodes: Vec<Node> = hugr.children(root).collect();
+    let mut out = String::new();
+    out.push_str(&nodes.iter().map(|&n| hugr.get_optype(n).name()).join(" "));
+    out.push('\n');
+    for &n1 in &nodes {
+        let row = nodes
+            .iter()
+            .map(|&n2| {
+                if hugr.node_connections(n1, n2).next().is_some() {
+                    "1"
+                } else {
+                    "0"
+                }
+            })
+            .join(" ");
+        out.push_str(&row);
+        out.push('\n');
+    }
+    out
+}
+
+/// The result of parsing a text adjacency matrix: the named operations (one
+/// per row, in row order) and the set of `(from, to)` row-index pairs with an
+/// edge between them.
+///
+/// This is the structural content of the matrix, ready to be replayed
+/// against a HUGR builder (`add_node`/`connect`) to reconstruct the region;
+/// actually materializing it is left to the caller's builder of choice, since
+/// building is independent of this read-only, `HugrView`-based module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AdjacencyMatrix {
+    /// The operation name for each row, or `None` if the row had no name in
+    /// the header (such rows should default to an opaque placeholder node).
+    pub op_names: Vec<Option<String>>,
+    /// `(from, to)` row-index pairs with an edge between them.
+    pub edges: Vec<(usize, usize)>,
+}
+
+/// Parses the text adjacency-matrix format produced by
+/// [`export_adjacency_matrix`].
+pub fn parse_adjacency_matrix(text: &str) -> Result<AdjacencyMatrix, AdjacencyParseError> {
+    let mut lines = text.lines().peekable();
+    let first = *lines.peek().ok_or(AdjacencyParseError::Empty)?;
+    // A non-empty first line made only of `0`/`1` entries is already a
+    // matrix row: there is no header, and no row has a name.
+    let is_row = !first.trim().is_empty()
+        && first.split_whitespace().all(|tok| tok == "0" || tok == "1");
+    let op_names: Vec<Option<String>> = if is_row {
+        vec![None; first.split_whitespace().count()]
+    } else {
+        lines.next();
+        first.split_whitespace().map(|tok| Some(tok.to_string())).collect()
+    };
+    let n = op_names.len();
+
+    let mut edges = Vec::new();
+    for (i, line) in lines.enumerate() {
+        if i >= n {
+            return Err(AdjacencyParseError::TooManyRows { expected: n });
+        }
+        let entries: Vec<&str> = line.split_whitespace().collect();
+        if entries.len() != n {
+            return Err(AdjacencyParseError::WrongRowLength {
+                row: i,
+                expected: n,
+                found: entries.len(),
+            });
+        }
+        for (j, entry) in entries.into_iter().enumerate() {
+            match entry {
+                "0" => {}
+                "1" => edges.push((i, j)),
+                other => {
+                    return Err(AdjacencyParseError::InvalidEntry {
+                        row: i,
+                        col: j,
+                        found: other.to_string(),
+                    })
+                }
+            }
+        }
+    }
+    Ok(AdjacencyMatrix { op_names, edges })
+}
+
+/// An error parsing the text adjacency-matrix format.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AdjacencyParseError {
+    #[error("input was empty - expected at least a header line")]
+    Empty,
+    #[error("matrix has more rows than the header declared ({expected})")]
+    TooManyRows { expected: usize },
+    #[error("row {row} has {found} entries, expected {expected}")]
+    WrongRowLength {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("row {row}, column {col}: expected '0' or '1', found {found:?}")]
+    InvalidEntry {
+        row: usize,
+        col: usize,
+        found: String,
+    },
+}
@@ -0,0 +1,64 @@
+//! Cycle detection over a dataflow region, used to report the actual
+//! offending path in a `NotADag` validation error rather than just "somewhere
+//! there is a cycle" (see `dfg_with_cycles`).
+use std::collections::HashMap;
+
+use crate::Node;
+
+use super::HugrView;
+
+/// Runs a DFS from each child of `parent`, looking for a back-edge. The DFS
+/// follows every [`HugrView::output_neighbours`] link of each node it
+/// reaches: edges are not filtered by kind (order edges are followed too),
+/// and a link that leaves the children of `parent` is followed out of the
+/// region. If a back-edge is found, returns the cycle it closes -
+/// the path from the back-edge's target up to (and including) its source, in
+/// traversal order.
+///
+/// Intended to populate `ValidationError::NotADag`'s cycle field, so callers
+/// debugging a generated HUGR see the actual loop instead of a bare
+/// "not a dag" error.
+pub fn find_cycle<'a>(hugr: &impl HugrView<'a>, parent: Node) -> Option<Vec<Node>> {
+    let mut state: HashMap<Node, u8> = HashMap::new();
+    let mut path: Vec<Node> = Vec::new();
+
+    for child in hugr.children(parent) {
+        if state.contains_key(&child) {
+            continue;
+        }
+        if let Some(cycle) = dfs(hugr, child, &mut state, &mut path) {
+            return Some(cycle);
+        }
+    }
+    None
+}
+
+/// `0` = unvisited, `1` = in progress (on `path`), `2` = fully explored.
+fn dfs<'a>(
+    hugr: &impl HugrView<'a>,
+    node: Node,
+    state: &mut HashMap<Node, u8>,
+    path: &mut Vec<Node>,
+) -> Option<Vec<Node>> {
+    state.insert(node, 1);
+    path.push(node);
+    for succ in hugr.output_neighbours(node) {
+        match state.get(&succ).copied().unwrap_or(0) {
+            1 => {
+                // Back-edge to `succ`, which is still on the stack: the
+                // cycle is the suffix of `path` starting there.
+                let start = path.iter().position(|n| *n == succ).unwrap();
+                return Some(path[start..].to_vec());
+            }
+            2 => continue,
+            _ => {
+                if let Some(cycle) = dfs(hugr, succ, state, path) {
+                    return Some(cycle);
+                }
+            }
+        }
+    }
+    path.pop();
+    state.insert(node, 2);
+    None
+}
@@ -0,0 +1,150 @@
+//! Automatic `Lift`-node insertion to repair extension mismatches.
+//!
+//! The `missing_lift_node`/`extensions_mismatch` validation tests show that
+//! wiring a port with fewer extension requirements into one demanding more
+//! fails with `ExtensionError::TgtExceedsSrcExtensionsAtPort` - "This could
+//! be fixed by adding a lift node." [`insert_lift_nodes`] does exactly that
+//! automatically.
+use std::collections::HashSet;
+
+use portgraph::{Direction, NodeIndex};
+
+use crate::hugr::{Hugr, HugrError};
+use crate::ops::{LeafOp, OpType};
+
+/// Walks the children of `parent` and, for every value edge whose target
+/// requires extensions the source's signature doesn't already provide,
+/// inserts a chain of [`LeafOp::Lift`] nodes - one per missing extension -
+/// splicing it into the edge. Never touches edges where the source has
+/// extensions the target doesn't require - that direction remains a
+/// validation error, since there the source should be narrowed instead of
+/// the target widened.
+///
+/// Returns the set of inserted `Lift` nodes, so a caller can undo the pass by
+/// removing them.
+pub fn insert_lift_nodes(
+    hugr: &mut Hugr,
+    parent: NodeIndex,
+) -> Result<HashSet<NodeIndex>, HugrError> {
+    let mut inserted = HashSet::new();
+
+    // Collect every incoming edge under `parent` up front: we can't walk
+    // `hugr.graph` while also splicing nodes into it below.
+    let edges: Vec<(NodeIndex, usize, NodeIndex, usize)> = hugr
+        .children(parent)
+        .flat_map(|tgt| incoming_edges(hugr, tgt))
+        .collect();
+
+    for (mut src, mut src_port, tgt, dst_port) in edges {
+        let src_sig = hugr.get_optype(src).signature();
+        let tgt_sig = hugr.get_optype(tgt).signature();
+        // What `tgt` needs that `src` doesn't already provide.
+        let missing = src_sig.extension_reqs.missing_from(&tgt_sig.extension_reqs);
+        if missing.iter().next().is_none() {
+            continue;
+        }
+        let type_row = vec![src_sig.output[src_port].clone()].into();
+
+        unlink_incoming(hugr, tgt, dst_port);
+
+        // Chain every missing extension's `Lift` onto the previous one,
+        // instead of rewiring the same edge from scratch each time and
+        // orphaning everything but the last.
+        for ext in missing.iter() {
+            let lift = hugr.add_node(OpType::LeafOp(LeafOp::Lift {
+                type_row: type_row.clone(),
+                new_extension: ext.clone(),
+            }));
+            hugr.set_parent(lift, parent)?;
+            hugr.connect(src, src_port, lift, 0)?;
+            inserted.insert(lift);
+            src = lift;
+            src_port = 0;
+        }
+        hugr.connect(src, src_port, tgt, dst_port)?;
+    }
+    Ok(inserted)
+}
+
+/// Returns `(src, src_port, tgt, dst_port)` for every incoming value edge of
+/// `tgt`.
+fn incoming_edges(hugr: &Hugr, tgt: NodeIndex) -> Vec<(NodeIndex, usize, NodeIndex, usize)> {
+    hugr.graph
+        .port_offsets(tgt, Direction::Incoming)
+        .filter_map(|dst_offset| {
+            let dst_port = hugr.graph.port_index(tgt, dst_offset)?;
+            let (_, link) = hugr.graph.port_links(dst_port).next()?;
+            let src_port = link.port();
+            let src = hugr.graph.port_node(src_port)?;
+            let src_offset = hugr.graph.port_offset(src_port)?;
+            Some((src, src_offset.index(), tgt, dst_offset.index()))
+        })
+        .collect()
+}
+
+/// Removes whatever is currently linked into `node`'s incoming port
+/// `port`, if anything.
+fn unlink_incoming(hugr: &mut Hugr, node: NodeIndex, port: usize) {
+    let port_index = hugr
+        .graph
+        .port_offsets(node, Direction::Incoming)
+        .find(|offset| offset.index() == port)
+        .and_then(|offset| hugr.graph.port_index(node, offset));
+    if let Some(port_index) = port_index {
+        hugr.graph.unlink_port(port_index);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::extension::ExtensionId;
+    use crate::types::Type;
+
+    #[test]
+    fn splices_a_lift_node_into_a_mismatched_edge() {
+        let mut hugr = Hugr::new();
+        let parent = hugr.root();
+        let ty = Type::new_unit();
+        let ext = ExtensionId::new_unchecked("foo");
+
+        // `src` has two outputs; connecting from the *second* one means a
+        // hardcoded port 0 would wire the wrong port.
+        let src = hugr.add_node(OpType::LeafOp(LeafOp::UnpackTuple {
+            tys: vec![ty.clone(), ty.clone()].into(),
+        }));
+        hugr.set_parent(src, parent).unwrap();
+        let tgt = hugr.add_node(OpType::LeafOp(LeafOp::Lift {
+            type_row: vec![ty.clone()].into(),
+            new_extension: ext.clone(),
+        }));
+        hugr.set_parent(tgt, parent).unwrap();
+        hugr.connect(src, 1, tgt, 0).unwrap();
+
+        let inserted = insert_lift_nodes(&mut hugr, parent).unwrap();
+        assert_eq!(inserted.len(), 1);
+        let lift = *inserted.iter().next().unwrap();
+
+        // `src`'s port 1 now feeds the inserted `Lift`, not `tgt` directly -
+        // the real port offset was threaded through, not hardcoded to 0.
+        let src_port = hugr
+            .graph
+            .port_offsets(src, Direction::Outgoing)
+            .nth(1)
+            .and_then(|o| hugr.graph.port_index(src, o))
+            .unwrap();
+        let (_, link) = hugr.graph.port_links(src_port).next().unwrap();
+        assert_eq!(hugr.graph.port_node(link.port()).unwrap(), lift);
+
+        // The `Lift` feeds `tgt` in turn, so the original edge wasn't left
+        // dangling on an orphaned path.
+        let tgt_port = hugr
+            .graph
+            .port_offsets(tgt, Direction::Incoming)
+            .next()
+            .and_then(|o| hugr.graph.port_index(tgt, o))
+            .unwrap();
+        let (_, link) = hugr.graph.port_links(tgt_port).next().unwrap();
+        assert_eq!(hugr.graph.port_node(link.port()).unwrap(), lift);
+    }
+}
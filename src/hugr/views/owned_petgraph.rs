@@ -0,0 +1,56 @@
+//! Owned [`petgraph`] materialization of a [`HugrView`], for algorithms (
+//! toposort, SCC/condensation, Dijkstra, connected components, ...) that need
+//! an owned `Graph`/`StableGraph` rather than the borrowing, visit-only
+//! [`PetgraphWrapper`](super::PetgraphWrapper).
+use petgraph::stable_graph::{NodeIndex as PetNodeIndex, StableGraph};
+use std::collections::HashMap;
+
+use crate::types::EdgeKind;
+use crate::{Direction, Node};
+
+use super::HugrView;
+
+/// An owned `petgraph` copy of a HUGR region, with node weights set to the
+/// HUGR [`Node`] they came from and edge weights set to the [`EdgeKind`] of
+/// the (outgoing) port the edge leaves from.
+///
+/// Keeps a reverse index so algorithm results (which come back as
+/// [`petgraph::graph::NodeIndex`]) can be translated back to HUGR [`Node`]s.
+pub struct OwnedPetgraph {
+    /// The materialized graph; node weights are the originating HUGR nodes.
+    pub graph: StableGraph<Node, EdgeKind>,
+    /// Maps a HUGR [`Node`] to its index in [`OwnedPetgraph::graph`].
+    pub node_indices: HashMap<Node, PetNodeIndex>,
+}
+
+impl OwnedPetgraph {
+    /// The `petgraph` index corresponding to a HUGR [`Node`], if it was
+    /// included in this graph.
+    pub fn node_index(&self, node: Node) -> Option<PetNodeIndex> {
+        self.node_indices.get(&node).copied()
+    }
+
+    /// The HUGR [`Node`] corresponding to a `petgraph` index.
+    pub fn hugr_node(&self, idx: PetNodeIndex) -> Node {
+        self.graph[idx]
+    }
+}
+
+pub(super) fn to_petgraph<'a>(hugr: &impl HugrView<'a>) -> OwnedPetgraph {
+    let mut graph = StableGraph::new();
+    let mut node_indices = HashMap::new();
+    for node in hugr.nodes() {
+        node_indices.insert(node, graph.add_node(node));
+    }
+    for node in hugr.nodes() {
+        for port in hugr.node_ports(node, Direction::Outgoing) {
+            let Some(kind) = hugr.get_optype(node).port_kind(port) else {
+                continue;
+            };
+            for (other, _) in hugr.linked_ports(node, port) {
+                graph.add_edge(node_indices[&node], node_indices[&other], kind.clone());
+            }
+        }
+    }
+    OwnedPetgraph { graph, node_indices }
+}
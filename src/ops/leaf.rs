@@ -4,7 +4,7 @@ use smol_str::SmolStr;
 
 use super::custom::ExternalOp;
 use super::{OpName, OpTag, OpTrait, StaticTag};
-use crate::extension::prelude::{QB_T, USIZE_T};
+use crate::extension::prelude::USIZE_T;
 use crate::type_row;
 use crate::{
     extension::{ExtensionId, ExtensionSet},
@@ -17,37 +17,14 @@ use crate::{
 #[serde(tag = "lop")]
 pub enum LeafOp {
     /// A user-defined operation that can be downcasted by the extensions that
-    /// define it.
+    /// define it. This is how gate sets (e.g. the `quantum` extension's
+    /// Clifford+T gates) are represented, rather than as variants here.
     CustomOp(Box<ExternalOp>),
-    /// A Hadamard gate.
-    H,
-    /// A T gate.
-    T,
-    /// An S gate.
-    S,
-    /// A Pauli X gate.
-    X,
-    /// A Pauli Y gate.
-    Y,
-    /// A Pauli Z gate.
-    Z,
-    /// An adjoint T gate.
-    Tadj,
-    /// An adjoint S gate.
-    Sadj,
-    /// A controlled X gate.
-    CX,
-    /// A maximally entangling ZZ phase gate.
-    ZZMax,
-    /// A qubit reset operation.
-    Reset,
     /// A no-op operation.
     Noop {
         /// The type of edges connecting the Noop.
         ty: Type,
     },
-    /// A qubit measurement operation.
-    Measure,
     /// A bitwise XOR operation.
     Xor,
     /// An operation that packs all its inputs into a tuple.
@@ -89,19 +66,7 @@ impl OpName for LeafOp {
     fn name(&self) -> SmolStr {
         match self {
             LeafOp::CustomOp(ext) => return ext.name(),
-            LeafOp::H => "H",
-            LeafOp::T => "T",
-            LeafOp::S => "S",
-            LeafOp::X => "X",
-            LeafOp::Y => "Y",
-            LeafOp::Z => "Z",
-            LeafOp::Tadj => "Tadj",
-            LeafOp::Sadj => "Sadj",
-            LeafOp::CX => "CX",
-            LeafOp::ZZMax => "ZZMax",
-            LeafOp::Reset => "Reset",
             LeafOp::Noop { ty: _ } => "Noop",
-            LeafOp::Measure => "Measure",
             LeafOp::Xor => "Xor",
             LeafOp::MakeTuple { tys: _ } => "MakeTuple",
             LeafOp::UnpackTuple { tys: _ } => "UnpackTuple",
@@ -121,19 +86,7 @@ impl OpTrait for LeafOp {
     fn description(&self) -> &str {
         match self {
             LeafOp::CustomOp(ext) => ext.description(),
-            LeafOp::H => "Hadamard gate",
-            LeafOp::T => "T gate",
-            LeafOp::S => "S gate",
-            LeafOp::X => "Pauli X gate",
-            LeafOp::Y => "Pauli Y gate",
-            LeafOp::Z => "Pauli Z gate",
-            LeafOp::Tadj => "Adjoint T gate",
-            LeafOp::Sadj => "Adjoint S gate",
-            LeafOp::CX => "Controlled X gate",
-            LeafOp::ZZMax => "Maximally entangling ZZPhase gate",
-            LeafOp::Reset => "Qubit reset",
             LeafOp::Noop { ty: _ } => "Noop gate",
-            LeafOp::Measure => "Qubit measurement gate",
             LeafOp::Xor => "Bitwise XOR",
             LeafOp::MakeTuple { tys: _ } => "MakeTuple operation",
             LeafOp::UnpackTuple { tys: _ } => "UnpackTuple operation",
@@ -156,19 +109,6 @@ impl OpTrait for LeafOp {
             LeafOp::Noop { ty: typ } => {
                 AbstractSignature::new_df(vec![typ.clone()], vec![typ.clone()])
             }
-            LeafOp::H
-            | LeafOp::Reset
-            | LeafOp::T
-            | LeafOp::S
-            | LeafOp::Tadj
-            | LeafOp::Sadj
-            | LeafOp::X
-            | LeafOp::Y
-            | LeafOp::Z => AbstractSignature::new_linear(type_row![QB_T]),
-            LeafOp::CX | LeafOp::ZZMax => AbstractSignature::new_linear(type_row![QB_T, QB_T]),
-            LeafOp::Measure => {
-                AbstractSignature::new_df(type_row![QB_T], type_row![QB_T, BIT_TYPE])
-            }
             LeafOp::Xor => {
                 AbstractSignature::new_df(type_row![BIT_TYPE, BIT_TYPE], type_row![BIT_TYPE])
             }
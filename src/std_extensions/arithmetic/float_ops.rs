@@ -117,6 +117,59 @@ pub fn extension() -> Extension {
     extension
 }
 
+/// The pure-`f64` semantics behind each `ConstFold`-able op in this
+/// extension. None of these are registered into a
+/// [`crate::extension::ConstFoldRegistry`] - doing so means reading a float
+/// out of a [`crate::ops::Const`] and wrapping the result back into one,
+/// and `Const`'s definition isn't part of this snapshot (see
+/// [`crate::extension::const_fold`] for the full story). Keeping the raw
+/// arithmetic here, tested directly against `f64` rather than through that
+/// missing plumbing, is what's actually achievable today; it's what a real
+/// `ConstFoldFn` for each of these ops would eventually call into.
+mod const_fold_ops {
+    /// `fadd`'s semantics: ordinary IEEE-754 addition, so e.g. `NaN + x` is
+    /// `NaN` for any `x`.
+    pub fn fadd(a: f64, b: f64) -> f64 {
+        a + b
+    }
+
+    /// `fmul`'s semantics.
+    pub fn fmul(a: f64, b: f64) -> f64 {
+        a * b
+    }
+
+    /// `fneg`'s semantics.
+    pub fn fneg(a: f64) -> f64 {
+        -a
+    }
+
+    /// `feq`'s semantics: IEEE-754 equality, under which `NaN == NaN` is
+    /// `false` (and so is `NaN == x` for any `x`, including itself).
+    pub fn feq(a: f64, b: f64) -> bool {
+        a == b
+    }
+
+    /// `flt`'s semantics: IEEE-754 `<`, which is `false` whenever either
+    /// operand is `NaN`.
+    pub fn flt(a: f64, b: f64) -> bool {
+        a < b
+    }
+
+    /// `fmax`'s semantics: like [`f64::max`], a `NaN` operand is ignored in
+    /// favour of the other (rather than propagating, unlike [`fadd`]), and
+    /// the result is only `NaN` if both operands are.
+    pub fn fmax(a: f64, b: f64) -> f64 {
+        a.max(b)
+    }
+
+    /// `fmin`'s semantics: like [`f64::min`], a `NaN` operand is ignored in
+    /// favour of the other, and the result is only `NaN` if both operands
+    /// are.
+    pub fn fmin(a: f64, b: f64) -> f64 {
+        a.min(b)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -130,4 +183,18 @@ mod test {
             assert!(name.starts_with('f'));
         }
     }
+
+    #[test]
+    fn test_const_fold_ops_nan_handling() {
+        use const_fold_ops::*;
+
+        assert!(fadd(f64::NAN, 1.0).is_nan());
+        assert!(!feq(f64::NAN, f64::NAN));
+        assert!(!flt(f64::NAN, 1.0));
+        assert!(!flt(1.0, f64::NAN));
+        assert_eq!(fmax(f64::NAN, 1.0), 1.0);
+        assert_eq!(fmin(f64::NAN, 1.0), 1.0);
+        assert!(fmax(f64::NAN, f64::NAN).is_nan());
+        assert_eq!(fneg(fmul(2.0, 3.0)), -6.0);
+    }
 }
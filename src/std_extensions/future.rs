@@ -0,0 +1,123 @@
+//! A `Future<T>` type and extension for out-of-band, lazily-resolved
+//! classical results.
+//!
+//! Some operations (e.g. a measurement dispatched to external hardware)
+//! produce their result asynchronously. Rather than blocking the graph at
+//! the point of issue, such an operation can return a `Future<T>` handle
+//! immediately; [`read`](extension) then forces it at whatever later point
+//! the value is actually needed, making the resolution point explicit in the
+//! graph instead of implicit in execution order.
+
+use smol_str::SmolStr;
+
+use crate::{
+    extension::{ExtensionId, SignatureError, TypeDefBound},
+    types::{
+        type_param::{TypeArg, TypeParam},
+        CustomType, FunctionType, Type, TypeBound,
+    },
+    Extension,
+};
+
+/// The extension identifier.
+pub const EXTENSION_ID: ExtensionId = ExtensionId::new_unchecked("future");
+
+/// The name of the `Future` type.
+pub const FUTURE_TYPE_NAME: SmolStr = SmolStr::new_inline("Future");
+
+/// Builds the `Future<T>` [`Type`] around the given element type.
+///
+/// Carries `elem`'s own [`TypeBound`], so a `Future` of a linear value is
+/// itself linear, and [`check_concrete_impl`](crate::extension::Extension)
+/// validation can tell the two apart.
+pub fn future_type(elem: Type) -> Type {
+    let bound = elem.least_upper_bound();
+    Type::new_extension(CustomType::new(
+        FUTURE_TYPE_NAME,
+        vec![TypeArg::Type { ty: elem }],
+        EXTENSION_ID,
+        bound,
+    ))
+}
+
+/// Recovers the `T` a `Future<T>` operation was instantiated with from its
+/// sole type argument.
+fn elem_type(arg_values: &[TypeArg]) -> Type {
+    match &arg_values[0] {
+        TypeArg::Type { ty } => ty.clone(),
+        _ => panic!("Future's sole type argument was not a Type"),
+    }
+}
+
+fn read_sig(arg_values: &[TypeArg]) -> Result<FunctionType, SignatureError> {
+    let t = elem_type(arg_values);
+    Ok(FunctionType::new(vec![future_type(t.clone())], vec![t]))
+}
+
+fn dup_sig(arg_values: &[TypeArg]) -> Result<FunctionType, SignatureError> {
+    let t = elem_type(arg_values);
+    Ok(FunctionType::new(
+        vec![future_type(t.clone())],
+        vec![future_type(t.clone()), future_type(t)],
+    ))
+}
+
+fn free_sig(arg_values: &[TypeArg]) -> Result<FunctionType, SignatureError> {
+    let t = elem_type(arg_values);
+    Ok(FunctionType::new(vec![future_type(t)], vec![]))
+}
+
+/// Extension defining `Future<T>`, together with `read`, `dup` and `free`,
+/// the operations needed to force, duplicate and discard a handle.
+pub fn extension() -> Extension {
+    let mut extension = Extension::new(EXTENSION_ID);
+
+    extension
+        .add_type(
+            FUTURE_TYPE_NAME,
+            vec![TypeParam::Type(TypeBound::Copyable)],
+            "A handle to a classical value computed out-of-band, not yet forced".to_owned(),
+            TypeDefBound::FromParams(vec![0]),
+        )
+        .unwrap();
+
+    extension
+        .add_op_custom_sig_simple(
+            "read".into(),
+            "Forces a Future<T>, blocking until its value is available".to_owned(),
+            vec![TypeParam::Type(TypeBound::Copyable)],
+            read_sig,
+        )
+        .unwrap();
+    extension
+        .add_op_custom_sig_simple(
+            "dup".into(),
+            "Duplicates a Future<T> into two handles to the same eventual value".to_owned(),
+            vec![TypeParam::Type(TypeBound::Copyable)],
+            dup_sig,
+        )
+        .unwrap();
+    extension
+        .add_op_custom_sig_simple(
+            "free".into(),
+            "Discards a Future<T> without forcing it".to_owned(),
+            vec![TypeParam::Type(TypeBound::Copyable)],
+            free_sig,
+        )
+        .unwrap();
+
+    extension
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_future_extension() {
+        let r = extension();
+        assert_eq!(r.name(), "future");
+        assert_eq!(r.types().count(), 1);
+        assert_eq!(r.operations().count(), 3);
+    }
+}
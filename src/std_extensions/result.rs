@@ -0,0 +1,125 @@
+//! A "result reporting" extension: consumes program values and reports them
+//! out under a static label, rather than leaving which values are observable
+//! to be inferred from graph structure (e.g. "whatever reaches an `Output`
+//! node").
+//!
+//! Each op is parametrised by a `tag` - a [`TypeArg::String`] - giving the
+//! label the value is reported under, so a compiler can declare "this wire
+//! is the observable result called `out0`" directly in the graph.
+
+use crate::{
+    extension::{prelude::BOOL_T, ExtensionId, ExtensionSet, SignatureError},
+    types::{
+        type_param::{TypeArg, TypeParam},
+        FunctionType, TypeBound,
+    },
+    Extension,
+};
+
+use super::arithmetic::float_types::FLOAT64_TYPE;
+
+/// The extension identifier.
+pub const EXTENSION_ID: ExtensionId = ExtensionId::new_unchecked("result");
+
+/// Recovers the `tag` a result op was instantiated with.
+fn tag(arg_values: &[TypeArg]) -> &str {
+    match &arg_values[0] {
+        TypeArg::String { value } => value,
+        _ => panic!("result op's first type argument was not a String"),
+    }
+}
+
+fn result_f64_sig(arg_values: &[TypeArg]) -> Result<FunctionType, SignatureError> {
+    let _ = tag(arg_values);
+    Ok(FunctionType::new(vec![FLOAT64_TYPE], vec![]))
+}
+
+fn result_bool_sig(arg_values: &[TypeArg]) -> Result<FunctionType, SignatureError> {
+    let _ = tag(arg_values);
+    Ok(FunctionType::new(vec![BOOL_T], vec![]))
+}
+
+/// Signature for the generic `result_value` op: reports a value of whatever
+/// [`Type`] it was instantiated with (e.g. an `int<n>` or array type defined
+/// by another extension), under the given tag.
+fn result_value_sig(arg_values: &[TypeArg]) -> Result<FunctionType, SignatureError> {
+    let _ = tag(arg_values);
+    let elem = match &arg_values[1] {
+        TypeArg::Type { ty } => ty.clone(),
+        _ => panic!("result_value's second type argument was not a Type"),
+    };
+    Ok(FunctionType::new(vec![elem], vec![]))
+}
+
+/// Extension declaring the observable results of a HUGR program: each op
+/// consumes one value and produces none, reporting the value out under a
+/// static string tag.
+pub fn extension() -> Extension {
+    let mut extension = Extension::new_with_reqs(
+        EXTENSION_ID,
+        ExtensionSet::singleton(&super::arithmetic::float_types::EXTENSION_ID),
+    );
+
+    extension
+        .add_op_custom_sig_simple(
+            "result_f64".into(),
+            "Reports a float64 value as a named result".to_owned(),
+            vec![TypeParam::String],
+            result_f64_sig,
+        )
+        .unwrap();
+    extension
+        .add_op_custom_sig_simple(
+            "result_bool".into(),
+            "Reports a bool value as a named result".to_owned(),
+            vec![TypeParam::String],
+            result_bool_sig,
+        )
+        .unwrap();
+    extension
+        .add_op_custom_sig_simple(
+            "result_value".into(),
+            "Reports a value of the given type as a named result".to_owned(),
+            vec![TypeParam::String, TypeParam::Type(TypeBound::Copyable)],
+            result_value_sig,
+        )
+        .unwrap();
+
+    extension
+}
+
+#[cfg(test)]
+mod test {
+    use crate::extension::{ExtensionRegistry, SignatureError, PRELUDE};
+    use crate::types::type_param::TypeArgError;
+
+    use super::*;
+
+    #[test]
+    fn test_result_extension() {
+        let r = extension();
+        assert_eq!(r.name(), "result");
+        assert_eq!(r.types().count(), 0);
+        assert_eq!(r.operations().count(), 3);
+        for (name, _) in r.operations() {
+            assert!(name.starts_with("result_"));
+        }
+    }
+
+    #[test]
+    fn test_result_tag_rejects_non_string_arg() {
+        let r = extension();
+        let reg: ExtensionRegistry = [PRELUDE.to_owned(), r.to_owned()].into();
+        let err =
+            r.instantiate_extension_op("result_bool", vec![TypeArg::BoundedNat { n: 0 }], &reg);
+        assert_eq!(
+            err,
+            Err(SignatureError::TypeArgMismatch(
+                TypeArgError::TypeMismatch {
+                    param: TypeParam::String,
+                    arg: TypeArg::BoundedNat { n: 0 },
+                }
+            ))
+        );
+    }
+}
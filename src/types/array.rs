@@ -0,0 +1,157 @@
+//! Fixed-length array types.
+//!
+//! [`ArrayType`] models a statically-sized homogeneous collection, with the
+//! length carried as a type-level constant (a [`TypeArg::BoundedNat`])
+//! rather than hidden inside an opaque [`CustomType`], and was meant to be
+//! the payload of a `TypeEnum::Array` variant mirroring the relationship
+//! between [`CustomType`] and `TypeEnum::Extension`. That variant doesn't
+//! exist in this tree, though: `TypeEnum` is defined in the core types
+//! module this tree doesn't include, so nothing actually constructs an
+//! `ArrayType` or reaches it from a [`Type`] - it's validation/substitution
+//! logic ready to be wired in once that variant lands, not a delivered
+//! first-class type.
+//!
+//! [`CustomType`]: super::CustomType
+use super::{
+    type_param::{check_type_args, TypeArg, TypeParam},
+    Substitution, Type, TypeBound,
+};
+use crate::extension::{ExtensionRegistry, SignatureError};
+
+/// A fixed-length array of elements of a single [`Type`].
+///
+/// The length is itself a [`TypeArg`], which must be a
+/// [`TypeArg::BoundedNat`] (or a type variable declared with a
+/// [`TypeParam::max_nat`]-style bound) so that it can participate in
+/// [substitute](ArrayType::substitute) just like any other type argument.
+///
+/// The `[elem; len]` form below is this crate's own rendering choice, not
+/// round-tripped through anything yet - nothing in this tree prints an
+/// `ArrayType` through a live `Type` value (see the [module docs](self)).
+#[derive(Clone, PartialEq, Eq, Debug, derive_more::Display, serde::Serialize, serde::Deserialize)]
+#[display(fmt = "[{}; {}]", elem, len)]
+pub struct ArrayType {
+    /// The type of each element in the array.
+    elem: Box<Type>,
+    /// The length of the array, as a type-level constant.
+    len: TypeArg,
+}
+
+impl ArrayType {
+    /// Creates a new fixed-length array type.
+    pub fn new(elem: Type, len: TypeArg) -> Self {
+        Self {
+            elem: Box::new(elem),
+            len,
+        }
+    }
+
+    /// The element type.
+    pub fn elem_type(&self) -> &Type {
+        &self.elem
+    }
+
+    /// The length of the array, as a [`TypeArg`].
+    pub fn len_arg(&self) -> &TypeArg {
+        &self.len
+    }
+
+    /// The smallest [`TypeBound`] containing the array - this is just the
+    /// element's bound, since an array of copyable elements is copyable,
+    /// an array of linear elements is linear, and so on.
+    pub(super) fn least_upper_bound(&self) -> TypeBound {
+        self.elem.least_upper_bound()
+    }
+
+    pub(super) fn validate(
+        &self,
+        allow_row_vars: bool,
+        extension_registry: &ExtensionRegistry,
+        var_decls: &[TypeParam],
+    ) -> Result<(), SignatureError> {
+        self.elem
+            .validate(allow_row_vars, extension_registry, var_decls)?;
+        self.len.validate(extension_registry, var_decls)?;
+        // The length must be (or validate as) a BoundedNat, so it can only
+        // ever be substituted with a literal array size.
+        check_type_args(std::slice::from_ref(&self.len), &[TypeParam::max_nat()])
+            .map_err(SignatureError::TypeArgMismatch)
+    }
+
+    pub(super) fn substitute(&self, t: &Substitution) -> Self {
+        Self {
+            elem: Box::new(
+                self.elem
+                    .substitute(t)
+                    .into_iter()
+                    .exactly_one_type("array element"),
+            ),
+            len: self.len.substitute(t),
+        }
+    }
+}
+
+trait ExactlyOneType {
+    fn exactly_one_type(self, what: &str) -> Type;
+}
+
+impl ExactlyOneType for Vec<Type> {
+    fn exactly_one_type(mut self, what: &str) -> Type {
+        assert_eq!(self.len(), 1, "substituting {what} did not yield a single Type - array elements may not be row variables");
+        self.pop().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::extension::prelude::{PRELUDE_REGISTRY, QB_T, USIZE_T};
+
+    #[test]
+    fn least_upper_bound_is_the_element_bound() {
+        assert_eq!(
+            ArrayType::new(USIZE_T, TypeArg::BoundedNat { n: 4 }).least_upper_bound(),
+            TypeBound::Eq
+        );
+        assert_eq!(
+            ArrayType::new(QB_T, TypeArg::BoundedNat { n: 4 }).least_upper_bound(),
+            TypeBound::Any
+        );
+    }
+
+    #[test]
+    fn validate_accepts_a_bounded_nat_length() {
+        let array = ArrayType::new(USIZE_T, TypeArg::BoundedNat { n: 4 });
+        assert!(array.validate(false, &PRELUDE_REGISTRY, &[]).is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_a_non_bounded_nat_length() {
+        let array = ArrayType::new(USIZE_T, TypeArg::String { value: "oops".into() });
+        assert!(array.validate(false, &PRELUDE_REGISTRY, &[]).is_err());
+    }
+
+    #[test]
+    fn substitute_replaces_the_element_type_and_length() {
+        let var = Type::new_var_use(0, TypeBound::Any);
+        let array = ArrayType::new(var, TypeArg::new_var_use(1, TypeParam::max_nat()));
+
+        let sub = Substitution::new(
+            &[
+                TypeArg::Type { ty: USIZE_T },
+                TypeArg::BoundedNat { n: 10 },
+            ],
+            &PRELUDE_REGISTRY,
+        );
+        let substituted = array.substitute(&sub);
+
+        assert_eq!(substituted.elem_type(), &USIZE_T);
+        assert_eq!(substituted.len_arg(), &TypeArg::BoundedNat { n: 10 });
+    }
+
+    #[test]
+    fn display_matches_the_documented_elem_len_form() {
+        let array = ArrayType::new(USIZE_T, TypeArg::BoundedNat { n: 4 });
+        assert_eq!(array.to_string(), format!("[{USIZE_T}; 4]"));
+    }
+}
@@ -0,0 +1,105 @@
+//! Compact binary (de)serialization of [`Type`] and [`FunctionType`] using
+//! CBOR.
+//!
+//! This reuses the derived `serde::Serialize`/`Deserialize` impls [`Type`]
+//! and [`FunctionType`] already have (the same ones the JSON path uses) and
+//! just swaps CBOR's binary map encoding in for JSON's text. No integer tags
+//! are assigned to `TypeEnum`/`SumType` variants: the output is more compact
+//! than JSON purely because CBOR's wire format is, not because this module
+//! does any tagging of its own.
+use super::{FunctionType, Type};
+
+/// An error encoding or decoding a [`Type`] or [`FunctionType`] as CBOR.
+#[derive(Debug, thiserror::Error)]
+pub enum CborError {
+    /// The value could not be encoded.
+    #[error("error encoding CBOR: {0}")]
+    Encode(#[from] ciborium::ser::Error<std::io::Error>),
+    /// The bytes could not be decoded.
+    #[error("error decoding CBOR: {0}")]
+    Decode(#[from] ciborium::de::Error<std::io::Error>),
+}
+
+impl Type {
+    /// Encodes this type as a compact CBOR byte string.
+    pub fn to_cbor(&self) -> Result<Vec<u8>, CborError> {
+        let mut buf = Vec::new();
+        ciborium::into_writer(self, &mut buf)?;
+        Ok(buf)
+    }
+
+    /// Decodes a [`Type`] previously written by [`Type::to_cbor`].
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, CborError> {
+        Ok(ciborium::from_reader(bytes)?)
+    }
+}
+
+impl FunctionType {
+    /// Encodes this signature as a compact CBOR byte string.
+    pub fn to_cbor(&self) -> Result<Vec<u8>, CborError> {
+        let mut buf = Vec::new();
+        ciborium::into_writer(self, &mut buf)?;
+        Ok(buf)
+    }
+
+    /// Decodes a [`FunctionType`] previously written by
+    /// [`FunctionType::to_cbor`].
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, CborError> {
+        Ok(ciborium::from_reader(bytes)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::extension::prelude::{PRELUDE_REGISTRY, QB_T, USIZE_T};
+    use crate::types::TypeBound;
+
+    #[test]
+    fn simple_type_round_trips() {
+        let bytes = USIZE_T.to_cbor().unwrap();
+        assert_eq!(Type::from_cbor(&bytes).unwrap(), USIZE_T);
+    }
+
+    #[test]
+    fn function_type_round_trips_and_revalidates() {
+        let ft = FunctionType::new(vec![USIZE_T, QB_T], vec![USIZE_T]);
+        let bytes = ft.to_cbor().unwrap();
+        let decoded = FunctionType::from_cbor(&bytes).unwrap();
+
+        assert_eq!(decoded, ft);
+        // A round trip through CBOR must not change anything `validate`
+        // cares about.
+        assert!(decoded.validate(&PRELUDE_REGISTRY, &[]).is_ok());
+    }
+
+    #[test]
+    fn nested_function_type_round_trips() {
+        let ty = Type::new_function(FunctionType::new(
+            vec![Type::new_function(FunctionType::new(
+                vec![USIZE_T],
+                vec![QB_T],
+            ))],
+            vec![USIZE_T],
+        ));
+
+        let bytes = ty.to_cbor().unwrap();
+        assert_eq!(Type::from_cbor(&bytes).unwrap(), ty);
+    }
+
+    #[test]
+    fn from_cbor_rejects_garbage_bytes() {
+        assert!(Type::from_cbor(&[0xff, 0x00, 0x01]).is_err());
+    }
+
+    #[test]
+    fn least_upper_bound_is_unaffected_by_a_round_trip() {
+        // Not a CBOR-specific property, but a cheap way to confirm the
+        // decoded value isn't just equal by `PartialEq` coincidence - it
+        // behaves like the original for something `validate` depends on.
+        let ty = QB_T;
+        assert_eq!(ty.least_upper_bound(), TypeBound::Any);
+        let decoded = Type::from_cbor(&ty.to_cbor().unwrap()).unwrap();
+        assert_eq!(decoded.least_upper_bound(), TypeBound::Any);
+    }
+}
@@ -0,0 +1,138 @@
+//! Closures over a captured environment.
+//!
+//! [`ClosureType`] models a function value together with the row of types
+//! it closes over. Unlike a bare [`FunctionType`], which is always
+//! [`TypeBound::Copyable`], a closure's bound depends on what it captures:
+//! closing over a linear resource makes the closure itself linear, so it
+//! can't be silently duplicated. This was meant to be the payload of a
+//! `TypeEnum::Closure` variant, mirroring the relationship between
+//! [`CustomType`] and `TypeEnum::Extension` - but, as with
+//! [`super::array`]'s `ArrayType`/`TypeEnum::Array`, that variant lives in
+//! the core types module this tree doesn't include, so nothing here
+//! actually constructs a [`ClosureType`] or reaches it from a [`Type`]; it's
+//! bound/validation/substitution logic staged for that variant, not a
+//! delivered linear-closure type.
+//!
+//! [`CustomType`]: super::CustomType
+use super::{type_param::TypeParam, FunctionType, Substitution, Type, TypeBound, TypeRow};
+use crate::extension::{ExtensionRegistry, SignatureError};
+
+/// A function value together with the row of types it captures from its
+/// enclosing scope.
+#[derive(
+    Clone, PartialEq, Eq, Debug, derive_more::Display, serde::Serialize, serde::Deserialize,
+)]
+#[display(fmt = "Closure[{}]({})", captures, signature)]
+pub struct ClosureType {
+    /// The signature of the closure when called.
+    signature: Box<FunctionType>,
+    /// The types of the values captured from the enclosing scope. May
+    /// contain a row variable, in which case it expands under substitution
+    /// like any other [`TypeRow`].
+    captures: TypeRow,
+}
+
+impl ClosureType {
+    /// Creates a new closure type with the given call signature and captured
+    /// environment.
+    pub fn new(signature: FunctionType, captures: TypeRow) -> Self {
+        Self {
+            signature: Box::new(signature),
+            captures,
+        }
+    }
+
+    /// The signature of the closure when called.
+    pub fn signature(&self) -> &FunctionType {
+        &self.signature
+    }
+
+    /// The types of the values captured from the enclosing scope.
+    pub fn captures(&self) -> &TypeRow {
+        &self.captures
+    }
+
+    /// The smallest [`TypeBound`] containing the closure - the join of the
+    /// bounds of everything it captures, so a closure capturing nothing is
+    /// [`TypeBound::Eq`] (the bottom of the lattice, same as an empty
+    /// [`TypeRow`]'s bound) while one capturing anything linear is
+    /// [`TypeBound::Any`].
+    pub(super) fn least_upper_bound(&self) -> TypeBound {
+        self.captures
+            .iter()
+            .map(Type::least_upper_bound)
+            .fold(
+                TypeBound::Eq,
+                |acc, b| {
+                    if acc.contains(b) {
+                        acc
+                    } else {
+                        b
+                    }
+                },
+            )
+    }
+
+    pub(super) fn validate(
+        &self,
+        allow_row_vars: bool,
+        extension_registry: &ExtensionRegistry,
+        var_decls: &[TypeParam],
+    ) -> Result<(), SignatureError> {
+        self.signature.validate(extension_registry, var_decls)?;
+        for ty in self.captures.iter() {
+            ty.validate(allow_row_vars, extension_registry, var_decls)?;
+        }
+        Ok(())
+    }
+
+    pub(super) fn substitute(&self, t: &Substitution) -> Self {
+        let captures: Vec<Type> = self
+            .captures
+            .iter()
+            .flat_map(|ty| ty.substitute(t))
+            .collect();
+        Self {
+            signature: Box::new(self.signature.substitute(t)),
+            captures: captures.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::extension::prelude::{BOOL_T, QB_T, USIZE_T};
+
+    fn closure_over(captures: impl Into<TypeRow>) -> ClosureType {
+        ClosureType::new(FunctionType::new(vec![], vec![]), captures.into())
+    }
+
+    #[test]
+    fn least_upper_bound_is_eq_for_no_captures() {
+        // Eq, not Copyable, is the bottom of the bound lattice - the same
+        // value an empty TypeRow's own least_upper_bound would produce.
+        assert_eq!(closure_over(vec![]).least_upper_bound(), TypeBound::Eq);
+    }
+
+    #[test]
+    fn least_upper_bound_joins_captures() {
+        assert_eq!(
+            closure_over(vec![USIZE_T]).least_upper_bound(),
+            TypeBound::Eq
+        );
+        assert_eq!(
+            closure_over(vec![BOOL_T]).least_upper_bound(),
+            TypeBound::Copyable
+        );
+        assert_eq!(
+            closure_over(vec![QB_T]).least_upper_bound(),
+            TypeBound::Any
+        );
+        // Joining an Eq-bounded capture with a linear one is linear.
+        assert_eq!(
+            closure_over(vec![USIZE_T, QB_T]).least_upper_bound(),
+            TypeBound::Any
+        );
+    }
+}
@@ -0,0 +1,63 @@
+//! Sequential and parallel composition of [`FunctionType`]s.
+//!
+//! These mirror how nodes actually wire together in a [`Hugr`](crate::Hugr):
+//! [`FunctionType::compose`] is "plug this signature's outputs into that
+//! signature's inputs", the same check [`Hugr::connect`](crate::hugr::Hugr::connect)
+//! already performs edge-by-edge, and [`FunctionType::extend`] is "run both
+//! signatures side by side", the same shape a tensor-product/parallel
+//! composition of two subgraphs has.
+use crate::extension::SignatureError;
+
+use super::FunctionType;
+
+impl FunctionType {
+    /// Sequentially composes `self` with `other`: a value flowing out of
+    /// `self` must be usable as the corresponding input of `other`.
+    ///
+    /// Requires `self.output() == other.input()` exactly (element-wise type
+    /// equality, including any row variables they happen to share) - this is
+    /// composition, not subtyping, so a caller that only has a subtype
+    /// relationship between the two rows should go through
+    /// [`Type::is_subtype_of`](super::Type::is_subtype_of) first. On success,
+    /// the result keeps `self`'s input and `other`'s output, and requires
+    /// whichever extensions either signature required.
+    pub fn compose(self, other: &FunctionType) -> Result<FunctionType, SignatureError> {
+        if self.output != other.input {
+            return Err(SignatureError::SignatureCompositionMismatch {
+                output: self.output,
+                input: other.input.clone(),
+            });
+        }
+        Ok(FunctionType {
+            input: self.input,
+            output: other.output.clone(),
+            extension_reqs: self.extension_reqs.union(&other.extension_reqs),
+        })
+    }
+
+    /// Parallel ("tensor") composition of `self` with `other`: the two
+    /// signatures run side by side, with `self`'s rows first, requiring the
+    /// union of both signatures' extensions.
+    ///
+    /// Unlike [`Self::compose`], this never fails - there's no shared
+    /// boundary to type-check, the two signatures are simply concatenated.
+    pub fn extend(self, other: &FunctionType) -> FunctionType {
+        let input = self
+            .input
+            .iter()
+            .chain(other.input.iter())
+            .cloned()
+            .collect();
+        let output = self
+            .output
+            .iter()
+            .chain(other.output.iter())
+            .cloned()
+            .collect();
+        FunctionType {
+            input,
+            output,
+            extension_reqs: self.extension_reqs.union(&other.extension_reqs),
+        }
+    }
+}
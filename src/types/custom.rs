@@ -4,7 +4,9 @@
 use smol_str::SmolStr;
 use std::fmt::{self, Display};
 
-use crate::extension::{ExtensionId, ExtensionRegistry, SignatureError};
+use std::collections::BTreeSet;
+
+use crate::extension::{ExtensionBound, ExtensionId, ExtensionRegistry, SignatureError};
 
 use super::{
     type_param::{TypeArg, TypeParam},
@@ -26,6 +28,11 @@ pub struct CustomType {
     args: Vec<TypeArg>,
     /// The [TypeBound] describing what can be done to instances of this type
     bound: TypeBound,
+    /// The set of named [ExtensionBound]s instances of this type advertise
+    /// they satisfy, beyond the fixed [TypeBound] lattice. Empty by default -
+    /// set via [CustomType::with_extension_bounds].
+    #[serde(default)]
+    extension_bounds: BTreeSet<ExtensionBound>,
 }
 
 impl CustomType {
@@ -41,6 +48,7 @@ impl CustomType {
             args: args.into(),
             extension: extension.into(),
             bound,
+            extension_bounds: BTreeSet::new(),
         }
     }
 
@@ -51,9 +59,23 @@ impl CustomType {
             args: vec![],
             extension,
             bound,
+            extension_bounds: BTreeSet::new(),
         }
     }
 
+    /// Builder-style setter for the [ExtensionBound]s this type's instances
+    /// advertise they satisfy (see [ExtensionRegistry::declare_bound_implies]
+    /// for how those bounds relate to each other).
+    pub fn with_extension_bounds(mut self, bounds: impl IntoIterator<Item = ExtensionBound>) -> Self {
+        self.extension_bounds = bounds.into_iter().collect();
+        self
+    }
+
+    /// The [ExtensionBound]s this type's instances advertise they satisfy.
+    pub fn extension_bounds(&self) -> &BTreeSet<ExtensionBound> {
+        &self.extension_bounds
+    }
+
     /// Returns the bound of this [`CustomType`].
     pub const fn bound(&self) -> TypeBound {
         self.bound
@@ -82,13 +104,20 @@ impl CustomType {
         def.check_custom(self)
     }
 
-    pub(super) fn substitute(&self, args: &[TypeArg]) -> Self {
+    pub(super) fn substitute(&self, args: &[TypeArg], extension_registry: &ExtensionRegistry) -> Self {
+        let new_args: Vec<TypeArg> = self.args.iter().map(|arg| arg.substitute(args)).collect();
+        // The bound may have narrowed as a result of substitution (e.g. a type
+        // variable bounded by `Any` getting replaced with a `Copyable` type),
+        // so recompute it from the TypeDef rather than keeping the old value.
+        let bound = extension_registry
+            .get(&self.extension)
+            .and_then(|ex| ex.get_type(&self.id))
+            .map_or(self.bound, |def| def.bound(&new_args));
         Self {
-            args: self.args.iter().map(|arg| arg.substitute(args)).collect(),
+            args: new_args,
+            bound,
             ..self.clone()
         }
-        // TODO the bound could get narrower as a result of substitution.
-        // But, we need the TypeDefBound (from the TypeDef in the Extension) to recalculate correctly...
     }
 }
 
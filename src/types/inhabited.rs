@@ -0,0 +1,44 @@
+//! Inhabitedness analysis for [`Type`] and [`SumType`].
+//!
+//! Follows the same recurrence as rustc's uninhabitedness check: a type is
+//! inhabited unless it can be proven, purely from its structure, to have no
+//! values. The main consumer is dead-code elimination - a [`Type::new_sum`]
+//! with no variants (the "never" type) is uninhabited, so any branch
+//! producing it is unreachable.
+use super::{SumType, Type, TypeEnum, TypeRow};
+
+impl Type {
+    /// Returns whether this type has any possible values.
+    ///
+    /// Conservatively returns `true` for [`TypeEnum::Variable`],
+    /// [`TypeEnum::RowVariable`], [`TypeEnum::Alias`], [`TypeEnum::Function`]
+    /// and [`TypeEnum::Extension`], since we cannot see inside them.
+    pub fn is_inhabited(&self) -> bool {
+        match self.as_type_enum() {
+            TypeEnum::Sum(s) => s.is_inhabited(),
+            TypeEnum::Extension(_)
+            | TypeEnum::Alias(_)
+            | TypeEnum::Function(_)
+            | TypeEnum::Variable(_, _)
+            | TypeEnum::RowVariable(_, _) => true,
+        }
+    }
+}
+
+impl SumType {
+    /// Returns whether this sum type has any possible values.
+    ///
+    /// A [`SumType::Unit`] is inhabited iff it has at least one variant.
+    /// A [`SumType::General`] is inhabited iff at least one of its variant
+    /// rows is inhabited, where a row is inhabited iff every type in it is.
+    pub fn is_inhabited(&self) -> bool {
+        match self {
+            SumType::Unit { size } => *size > 0,
+            SumType::General { rows } => rows.iter().any(row_is_inhabited),
+        }
+    }
+}
+
+fn row_is_inhabited(row: &TypeRow) -> bool {
+    row.iter().all(Type::is_inhabited)
+}
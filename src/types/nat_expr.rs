@@ -0,0 +1,279 @@
+//! Symbolic arithmetic over nat-kinded [TypeArg]s.
+//!
+//! [CustomType::new] only accepts a concrete `TypeArg::BoundedNat { n }` for
+//! each nat-kinded argument, so a container parameterized by `n+1` or `2*m`
+//! can't be expressed even when `m` is a declared nat variable (a
+//! [TypeArg::new_var_use] of [TypeParam::max_nat]/[TypeParam::bounded_nat]).
+//! [NatExpr] is a small expression language over such arguments - `Const`,
+//! `Var` (a De Bruijn reference, matching [TypeArg::new_var_use]'s `idx`) and
+//! `Plus`/`Mul` over subexpressions - together with a [NatExpr::normalize]
+//! that reduces any expression to a canonical sorted-monomial polynomial form,
+//! so that e.g. `(m + 1) + m` and `2*m + 1` compare equal.
+//!
+//! [CustomType::new]: super::custom::CustomType::new
+//! [TypeParam::max_nat]: super::type_param::TypeParam::max_nat
+//! [TypeParam::bounded_nat]: super::type_param::TypeParam::bounded_nat
+use std::collections::BTreeMap;
+
+use super::type_param::{TypeArg, TypeParam};
+use super::Substitution;
+
+/// An expression over nat-kinded type arguments: a constant, a reference to a
+/// declared nat type variable (by De Bruijn index, as in
+/// [TypeArg::new_var_use]), or a sum/product of subexpressions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NatExpr {
+    /// A concrete, already-known value.
+    Const(u64),
+    /// A reference to the `idx`'th in-scope nat type variable.
+    Var(usize),
+    /// The sum of two subexpressions.
+    Plus(Box<NatExpr>, Box<NatExpr>),
+    /// The product of two subexpressions.
+    Mul(Box<NatExpr>, Box<NatExpr>),
+}
+
+impl NatExpr {
+    /// Shorthand for [NatExpr::Plus].
+    pub fn plus(self, rhs: NatExpr) -> Self {
+        NatExpr::Plus(Box::new(self), Box::new(rhs))
+    }
+
+    /// Shorthand for [NatExpr::Mul].
+    pub fn mul(self, rhs: NatExpr) -> Self {
+        NatExpr::Mul(Box::new(self), Box::new(rhs))
+    }
+
+    /// True iff every variable this expression mentions is `< num_decls`,
+    /// mirroring the free-type-variable check used to validate a
+    /// [TypeArg::new_var_use] against the enclosing `PolyFuncType`'s
+    /// parameter list: an expression with an out-of-scope variable can never
+    /// be evaluated, however it's later substituted.
+    pub fn is_const_evaluatable(&self, num_decls: usize) -> bool {
+        match self {
+            NatExpr::Const(_) => true,
+            NatExpr::Var(idx) => *idx < num_decls,
+            NatExpr::Plus(l, r) | NatExpr::Mul(l, r) => {
+                l.is_const_evaluatable(num_decls) && r.is_const_evaluatable(num_decls)
+            }
+        }
+    }
+
+    /// Reduces this expression to canonical polynomial form: a sum of
+    /// monomials, each a coefficient times a sorted product of variables,
+    /// with monomials sorted by their variable multiset. Two expressions that
+    /// are semantically equal (e.g. `(m + 1) + m` and `2*m + 1`) normalize to
+    /// the same [Polynomial], regardless of how they were originally written.
+    ///
+    /// Errors with [NatExprError::Overflow] if combining coefficients along
+    /// the way would overflow `u64` - this can happen here even for
+    /// expressions with no free variables, since normalization folds
+    /// constants before [Self::eval] ever gets a chance to.
+    pub fn normalize(&self) -> Result<Polynomial, NatExprError> {
+        match self {
+            NatExpr::Const(n) => Ok(Polynomial::constant(*n)),
+            NatExpr::Var(idx) => Ok(Polynomial::from_monomial(1, vec![*idx])),
+            NatExpr::Plus(l, r) => l.normalize()?.add(&r.normalize()?),
+            NatExpr::Mul(l, r) => l.normalize()?.mul(&r.normalize()?),
+        }
+    }
+
+    /// Evaluates this expression given a binding of in-scope variables to
+    /// concrete `TypeArg::BoundedNat` values (as produced by substitution with
+    /// [TypeArg]s). Variables with no entry in `args`, or bound to a
+    /// non-concrete `TypeArg` (e.g. a still-free `new_var_use`), are left
+    /// symbolic: if every variable turns out to be bound, the result is
+    /// [EvalResult::Concrete]; otherwise the constant parts are folded and the
+    /// unresolved remainder is returned as [EvalResult::Partial].
+    pub fn eval(&self, args: &[TypeArg]) -> Result<EvalResult, NatExprError> {
+        let poly = self.normalize()?;
+        let mut total: u64 = 0;
+        let mut remainder = Polynomial::zero();
+        for (vars, coeff) in &poly.monomials {
+            match resolve_monomial(vars, args)? {
+                Some(value) => {
+                    let scaled = coeff.checked_mul(value).ok_or(NatExprError::Overflow)?;
+                    total = total.checked_add(scaled).ok_or(NatExprError::Overflow)?;
+                }
+                None => remainder.monomials.push((vars.clone(), *coeff)),
+            }
+        }
+        if remainder.monomials.is_empty() {
+            Ok(EvalResult::Concrete(total))
+        } else {
+            remainder.monomials.push((vec![], total));
+            Ok(EvalResult::Partial(remainder.canonicalize()?))
+        }
+    }
+
+    /// Resolves this expression against a [`Substitution`], the same way
+    /// [`Type::substitute`](super::Type::substitute) resolves a type - each
+    /// [NatExpr::Var] is looked up via [Substitution::apply_var] with a
+    /// [TypeParam::max_nat] bound, and must itself come back as a
+    /// `TypeArg::BoundedNat`, since a `Substitution` only ever maps a
+    /// declared nat variable to a concrete value. The critical invariant is
+    /// that once every variable an expression mentions is in the
+    /// substitution's scope, this succeeds unless the arithmetic overflows
+    /// `u64` (then [NatExprError::Overflow]) and is deterministic, so
+    /// that e.g. `n + 1` and `1 + n` evaluate to the same value once `n` is
+    /// bound.
+    pub fn eval_with_subst(&self, subst: &Substitution) -> Result<u64, NatExprError> {
+        match self {
+            NatExpr::Const(n) => Ok(*n),
+            NatExpr::Var(idx) => match subst.apply_var(*idx, &TypeParam::max_nat()) {
+                TypeArg::BoundedNat { n } => Ok(n),
+                _ => Err(NatExprError::NotANat),
+            },
+            NatExpr::Plus(l, r) => l
+                .eval_with_subst(subst)?
+                .checked_add(r.eval_with_subst(subst)?)
+                .ok_or(NatExprError::Overflow),
+            NatExpr::Mul(l, r) => l
+                .eval_with_subst(subst)?
+                .checked_mul(r.eval_with_subst(subst)?)
+                .ok_or(NatExprError::Overflow),
+        }
+    }
+}
+
+/// Resolves a monomial's variables against `args`, returning the product of
+/// their concrete values if *all* are bound to a `TypeArg::BoundedNat`, or
+/// `None` if any remain symbolic.
+fn resolve_monomial(vars: &[usize], args: &[TypeArg]) -> Result<Option<u64>, NatExprError> {
+    let mut product: u64 = 1;
+    for &idx in vars {
+        match args.get(idx) {
+            Some(TypeArg::BoundedNat { n }) => {
+                product = product.checked_mul(*n).ok_or(NatExprError::Overflow)?;
+            }
+            _ => return Ok(None),
+        }
+    }
+    Ok(Some(product))
+}
+
+/// The result of [NatExpr::eval].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EvalResult {
+    /// Every variable was bound: the expression reduced to a single value.
+    Concrete(u64),
+    /// Some variables remain free: the constant parts have been folded into
+    /// the returned, still-symbolic polynomial.
+    Partial(Polynomial),
+}
+
+/// An error evaluating a [NatExpr].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum NatExprError {
+    /// A partial sum/product of monomial coefficients overflowed `u64`.
+    #[error("nat expression overflowed u64 during evaluation")]
+    Overflow,
+    /// A variable resolved, via a [`Substitution`], to a [`TypeArg`] other
+    /// than `TypeArg::BoundedNat`.
+    #[error("nat expression variable resolved to a non-nat TypeArg")]
+    NotANat,
+}
+
+/// A nat expression in canonical polynomial form: a sum of monomials, each a
+/// `u64` coefficient times a product of variables (given as a sorted list of
+/// De Bruijn indices, with repeats for higher powers). Monomials are sorted
+/// and coefficients of equal monomials combined, so `==` on two
+/// [Polynomial]s is true iff the expressions they came from are semantically
+/// equal (over these `Plus`/`Mul`/`Const`/`Var` operations).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Polynomial {
+    monomials: Vec<(Vec<usize>, u64)>,
+}
+
+impl Polynomial {
+    fn zero() -> Self {
+        Self::default()
+    }
+
+    fn constant(n: u64) -> Self {
+        if n == 0 {
+            Self::zero()
+        } else {
+            Self::from_monomial(n, vec![])
+        }
+    }
+
+    fn from_monomial(coeff: u64, mut vars: Vec<usize>) -> Self {
+        vars.sort_unstable();
+        Self {
+            monomials: vec![(vars, coeff)],
+        }
+    }
+
+    fn add(&self, other: &Self) -> Result<Self, NatExprError> {
+        let mut merged = self.monomials.clone();
+        merged.extend(other.monomials.iter().cloned());
+        Self { monomials: merged }.canonicalize()
+    }
+
+    fn mul(&self, other: &Self) -> Result<Self, NatExprError> {
+        let mut merged = Vec::with_capacity(self.monomials.len() * other.monomials.len());
+        for (lvars, lcoeff) in &self.monomials {
+            for (rvars, rcoeff) in &other.monomials {
+                let mut vars = lvars.clone();
+                vars.extend(rvars.iter().copied());
+                vars.sort_unstable();
+                let coeff = lcoeff.checked_mul(*rcoeff).ok_or(NatExprError::Overflow)?;
+                merged.push((vars, coeff));
+            }
+        }
+        Self { monomials: merged }.canonicalize()
+    }
+
+    /// Combines monomials with the same variable multiset and drops any whose
+    /// combined coefficient is zero, then sorts the result so that equal
+    /// polynomials always have an identical `monomials` ordering.
+    fn canonicalize(mut self) -> Result<Self, NatExprError> {
+        let mut by_vars: BTreeMap<Vec<usize>, u64> = BTreeMap::new();
+        for (vars, coeff) in self.monomials.drain(..) {
+            let entry = by_vars.entry(vars).or_insert(0);
+            *entry = entry.checked_add(coeff).ok_or(NatExprError::Overflow)?;
+        }
+        let mut monomials: Vec<(Vec<usize>, u64)> = by_vars
+            .into_iter()
+            .filter(|(_, coeff)| *coeff != 0)
+            .collect();
+        monomials.sort();
+        Ok(Self { monomials })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mul_overflow_errors_instead_of_wrapping() {
+        // (2^32) * (2^32) overflows u64 rather than wrapping to 0.
+        let big = NatExpr::Const(1u64 << 32);
+        let expr = big.clone().mul(big);
+        assert_eq!(expr.normalize(), Err(NatExprError::Overflow));
+        assert_eq!(expr.eval(&[]), Err(NatExprError::Overflow));
+    }
+
+    #[test]
+    fn canonicalize_add_overflow_errors_instead_of_wrapping() {
+        // u64::MAX + 1, both as the same monomial (bare `Var(0)`), overflows
+        // when canonicalize sums their coefficients.
+        let max = NatExpr::Var(0).mul(NatExpr::Const(u64::MAX));
+        let expr = max.plus(NatExpr::Var(0));
+        assert_eq!(expr.normalize(), Err(NatExprError::Overflow));
+    }
+
+    #[test]
+    fn normalize_succeeds_without_overflow() {
+        // (m + 1) + m and 2*m + 1 both normalize to the same polynomial.
+        let a = NatExpr::Var(0)
+            .plus(NatExpr::Const(1))
+            .plus(NatExpr::Var(0));
+        let b = NatExpr::Const(2)
+            .mul(NatExpr::Var(0))
+            .plus(NatExpr::Const(1));
+        assert_eq!(a.normalize().unwrap(), b.normalize().unwrap());
+    }
+}
@@ -0,0 +1,458 @@
+//! `Display`/`FromStr` for [`FunctionType`], so tests and snapshot tooling can
+//! write an expected signature as a string instead of stitching together
+//! [`TypeRow`]s and [`Type`]s by hand.
+//!
+//! The textual form is `input -> ext_reqs output`, e.g.
+//! `prelude.qubit:A -> ["quantum"] prelude.qubit:A` or
+//! `(prelude.qubit:A, prelude.qubit:A) -> [] (prelude.qubit:A, prelude.qubit:A)`:
+//!
+//! - A [`TypeRow`] of one element is written bare; more than one is
+//!   parenthesised and comma-separated.
+//! - The extension requirements print the same way
+//!   [`ExtensionSet`](crate::extension::ExtensionSet)'s own `Display` does -
+//!   a bracketed, comma-separated list of quoted extension names.
+//! - A [`CustomType`] prints as `extension.name:B` (`extension.name:B(args)`
+//!   if it has any), where `B` is its [`TypeBound`] in the same `E`/`C`/`A`
+//!   form used for type variables below - the bound isn't derivable from
+//!   `extension.name` alone without an [`ExtensionRegistry`](crate::extension::ExtensionRegistry) to look its
+//!   [`TypeDef`](crate::extension::TypeDef) up, and dropping it silently
+//!   produced a [`CustomType`] that didn't round-trip (`PartialEq` includes
+//!   `bound`, so parsing back always reconstructed an `Any`-bounded type
+//!   regardless of the original). This otherwise keeps the extension id
+//!   alongside the name, as [`super::sexpr`]'s textual export does (there as
+//!   two separate atoms), rather than [`PolyFuncType`]'s `to_named_string` (which drops
+//!   the extension id - readable, but not something a parser could recover
+//!   a [`CustomType`] from).
+//! - A type variable prints as `#idx` followed by its [`TypeBound`] as a
+//!   single letter (`E`/`C`/`A`, the same symbols [`super::sexpr`] uses); a
+//!   row variable is the same with a trailing `..`.
+//! - A nested [`TypeEnum::Function`] is parenthesised and recurses.
+//! - [`SumType::Unit`] prints as `Sum[size]`.
+//!
+//! [`SumType::General`] is *not* round-tripped: its rendering would be
+//! indistinguishable from a parenthesised multi-element [`TypeRow`], and
+//! [`TypeEnum::Alias`] has no printable representation available outside
+//! [`super`] - both parse back as [`ParseError::Unsupported`] rather than
+//! silently producing the wrong type.
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use itertools::Itertools;
+
+use super::type_param::TypeArg;
+use super::{CustomType, FunctionType, SumType, Type, TypeBound, TypeEnum};
+use crate::extension::{ExtensionId, ExtensionSet};
+
+impl Display for FunctionType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} -> {} {}",
+            RowDisplay(self.input()),
+            self.extension_reqs,
+            RowDisplay(self.output())
+        )
+    }
+}
+
+struct RowDisplay<'a>(&'a [Type]);
+
+impl Display for RowDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.0 {
+            [ty] => write!(f, "{}", DisplayType(ty)),
+            row => write!(f, "({})", row.iter().map(DisplayType).join(", ")),
+        }
+    }
+}
+
+/// Renders a [`Type`] in the textual form this module's [`FromStr for
+/// FunctionType`](FunctionType) parses back.
+///
+/// This is a wrapper rather than `impl Display for Type` because `Type`
+/// already has a `Display` impl elsewhere that `HugrError`/`ValidationError`
+/// rely on (see `src/hugr.rs`'s `thiserror` messages) - a second inherent
+/// impl here would conflict with it.
+struct DisplayType<'a>(&'a Type);
+
+impl Display for DisplayType<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.0.as_type_enum() {
+            TypeEnum::Variable(idx, bound) => write!(f, "#{idx}{}", bound_symbol(*bound)),
+            TypeEnum::RowVariable(idx, bound) => write!(f, "#{idx}{}..", bound_symbol(*bound)),
+            TypeEnum::Extension(custy) => write!(f, "{}", DisplayCustomType(custy)),
+            TypeEnum::Function(ft) => write!(f, "({ft})"),
+            TypeEnum::Sum(SumType::Unit { size }) => write!(f, "Sum[{size}]"),
+            TypeEnum::Sum(SumType::General { .. }) => write!(f, "<unsupported sum type>"),
+            TypeEnum::Alias(a) => write!(f, "<unsupported alias {a:?}>"),
+        }
+    }
+}
+
+/// Renders a [`CustomType`] in this module's textual form (`extension.name:B`
+/// or `extension.name:B(args)`).
+///
+/// A wrapper rather than `impl Display for CustomType` for the same reason
+/// as [`DisplayType`]: `CustomType` already has a `Display` impl (see
+/// `src/types/custom.rs`) that this module's format doesn't match (it prints
+/// [`TypeArg`]s recursively through [`DisplayType`] instead of `{:?}`).
+struct DisplayCustomType<'a>(&'a CustomType);
+
+impl Display for DisplayCustomType<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}:{}",
+            self.0.extension(),
+            self.0.name(),
+            bound_symbol(self.0.bound())
+        )?;
+        if !self.0.args().is_empty() {
+            write!(
+                f,
+                "({})",
+                self.0.args().iter().map(display_arg).join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn display_arg(arg: &TypeArg) -> String {
+    match arg {
+        TypeArg::Type { ty } => DisplayType(ty).to_string(),
+        TypeArg::BoundedNat { n } => n.to_string(),
+        TypeArg::String { value } => format!("{value:?}"),
+        TypeArg::Sequence { elems } => format!("[{}]", elems.iter().map(display_arg).join(", ")),
+        other => format!("<unsupported arg {other:?}>"),
+    }
+}
+
+fn bound_symbol(bound: TypeBound) -> &'static str {
+    match bound {
+        TypeBound::Eq => "E",
+        TypeBound::Copyable => "C",
+        TypeBound::Any => "A",
+    }
+}
+
+fn parse_bound_symbol(s: &str) -> Option<TypeBound> {
+    match s {
+        "E" => Some(TypeBound::Eq),
+        "C" => Some(TypeBound::Copyable),
+        "A" => Some(TypeBound::Any),
+        _ => None,
+    }
+}
+
+/// An error parsing the textual form [`Display for FunctionType`](FunctionType)
+/// produces.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum ParseError {
+    #[error("unexpected end of input, expected {expected}")]
+    UnexpectedEof { expected: &'static str },
+    #[error("expected {expected} at {remaining:?}")]
+    Expected {
+        expected: &'static str,
+        remaining: String,
+    },
+    #[error("'{0}' is not a valid type bound (expected one of E, C, A)")]
+    InvalidBound(String),
+    #[error("{0} is not parseable back into a Type")]
+    Unsupported(String),
+}
+
+/// A cursor over the remaining, not-yet-parsed input.
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str) -> Self {
+        Self {
+            rest: s.trim_start(),
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest.chars().next()
+    }
+
+    fn eat_literal(&mut self, lit: &'static str) -> Result<(), ParseError> {
+        self.skip_ws();
+        if let Some(rest) = self.rest.strip_prefix(lit) {
+            self.rest = rest;
+            Ok(())
+        } else {
+            Err(ParseError::Expected {
+                expected: lit,
+                remaining: self.rest.to_string(),
+            })
+        }
+    }
+
+    fn eat_ident(&mut self) -> Result<&'a str, ParseError> {
+        self.skip_ws();
+        let end = self
+            .rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(self.rest.len());
+        if end == 0 {
+            return Err(ParseError::Expected {
+                expected: "identifier",
+                remaining: self.rest.to_string(),
+            });
+        }
+        let (ident, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Ok(ident)
+    }
+
+    fn eat_nat(&mut self) -> Result<u64, ParseError> {
+        self.skip_ws();
+        // Digits only, so that a type variable's `#0A` stops before its bound.
+        let end = self
+            .rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.rest.len());
+        let (digits, rest) = self.rest.split_at(end);
+        let n = digits.parse().map_err(|_| ParseError::Expected {
+            expected: "a number",
+            remaining: self.rest.to_string(),
+        })?;
+        self.rest = rest;
+        Ok(n)
+    }
+
+    fn eat_quoted_string(&mut self) -> Result<String, ParseError> {
+        self.skip_ws();
+        if self.peek() != Some('"') {
+            return Err(ParseError::Expected {
+                expected: "a quoted string",
+                remaining: self.rest.to_string(),
+            });
+        }
+        let end = self.rest[1..].find('"').ok_or(ParseError::UnexpectedEof {
+            expected: "closing '\"'",
+        })?;
+        let value = self.rest[1..1 + end].to_string();
+        self.rest = &self.rest[2 + end..];
+        Ok(value)
+    }
+
+    /// Parses a comma-separated `inside(...)` of `item`, consuming both
+    /// delimiters.
+    fn eat_parenthesised<T>(
+        &mut self,
+        open: char,
+        close: char,
+        mut item: impl FnMut(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        self.skip_ws();
+        if self.peek() != Some(open) {
+            return Err(ParseError::Expected {
+                expected: "opening delimiter",
+                remaining: self.rest.to_string(),
+            });
+        }
+        self.rest = &self.rest[open.len_utf8()..];
+        let mut items = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some(close) {
+            self.rest = &self.rest[close.len_utf8()..];
+            return Ok(items);
+        }
+        loop {
+            items.push(item(self)?);
+            self.skip_ws();
+            match self.peek() {
+                Some(',') => {
+                    self.rest = &self.rest[1..];
+                }
+                Some(c) if c == close => {
+                    self.rest = &self.rest[close.len_utf8()..];
+                    break;
+                }
+                _ => {
+                    return Err(ParseError::Expected {
+                        expected: "',' or closing delimiter",
+                        remaining: self.rest.to_string(),
+                    })
+                }
+            }
+        }
+        Ok(items)
+    }
+
+    fn parse_extension_set(&mut self) -> Result<ExtensionSet, ParseError> {
+        let names = self.eat_parenthesised('[', ']', |p| p.eat_quoted_string())?;
+        Ok(names.into_iter().map(ExtensionId::new_unchecked).collect())
+    }
+
+    fn parse_row(&mut self) -> Result<Vec<Type>, ParseError> {
+        self.skip_ws();
+        if self.peek() == Some('(') {
+            self.eat_parenthesised('(', ')', |p| p.parse_type())
+        } else {
+            Ok(vec![self.parse_type()?])
+        }
+    }
+
+    fn parse_type_arg(&mut self) -> Result<TypeArg, ParseError> {
+        self.skip_ws();
+        match self.peek() {
+            Some('"') => Ok(TypeArg::String {
+                value: self.eat_quoted_string()?,
+            }),
+            Some('[') => {
+                let elems = self.eat_parenthesised('[', ']', |p| p.parse_type_arg())?;
+                Ok(TypeArg::Sequence { elems })
+            }
+            Some(c) if c.is_ascii_digit() => Ok(TypeArg::BoundedNat { n: self.eat_nat()? }),
+            _ => Ok(TypeArg::Type {
+                ty: self.parse_type()?,
+            }),
+        }
+    }
+
+    fn parse_type(&mut self) -> Result<Type, ParseError> {
+        self.skip_ws();
+        match self.peek() {
+            Some('#') => {
+                self.rest = &self.rest[1..];
+                let idx = self.eat_nat()? as usize;
+                let symbol = self.eat_ident()?;
+                let bound = parse_bound_symbol(symbol)
+                    .ok_or_else(|| ParseError::InvalidBound(symbol.to_string()))?;
+                self.skip_ws();
+                if let Some(rest) = self.rest.strip_prefix("..") {
+                    self.rest = rest;
+                    Ok(Type::new_row_var_use(idx, bound))
+                } else {
+                    Ok(Type::new_var_use(idx, bound))
+                }
+            }
+            Some('(') => {
+                let ft = self.parse_function_type()?;
+                self.eat_literal(")")?;
+                Ok(Type::new_function(ft))
+            }
+            Some(c) if c.is_alphabetic() => {
+                let ident = self.eat_ident()?;
+                if ident == "Sum" {
+                    self.eat_literal("[")?;
+                    let size = self.eat_nat()?;
+                    self.eat_literal("]")?;
+                    return Ok(Type::new_unit_sum(size));
+                }
+                self.eat_literal(".")?;
+                let name = self.eat_ident()?;
+                self.eat_literal(":")?;
+                let bound_symbol = self.eat_ident()?;
+                let bound = parse_bound_symbol(bound_symbol)
+                    .ok_or_else(|| ParseError::InvalidBound(bound_symbol.to_string()))?;
+                self.skip_ws();
+                let args = if self.peek() == Some('(') {
+                    self.eat_parenthesised('(', ')', |p| p.parse_type_arg())?
+                } else {
+                    Vec::new()
+                };
+                Ok(Type::new_extension(CustomType::new(
+                    name,
+                    args,
+                    ExtensionId::new_unchecked(ident),
+                    bound,
+                )))
+            }
+            _ => Err(ParseError::Unsupported(self.rest.to_string())),
+        }
+    }
+
+    fn parse_function_type(&mut self) -> Result<FunctionType, ParseError> {
+        let input = self.parse_row()?;
+        self.eat_literal("->")?;
+        let extension_reqs = self.parse_extension_set()?;
+        let output = self.parse_row()?;
+        Ok(FunctionType::new(input, output).with_extension_delta(&extension_reqs))
+    }
+}
+
+impl FromStr for FunctionType {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        let ft = parser.parse_function_type()?;
+        parser.skip_ws();
+        if !parser.rest.is_empty() {
+            return Err(ParseError::Expected {
+                expected: "end of input",
+                remaining: parser.rest.to_string(),
+            });
+        }
+        Ok(ft)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::extension::prelude::{BOOL_T, QB_T, USIZE_T};
+
+    /// A handful of concrete leaf [`Type`]s this module's `Display`/`FromStr`
+    /// round-trips exactly, used instead of deriving `Arbitrary` over the
+    /// full [`TypeEnum`] - [`SumType::General`] and [`TypeEnum::Alias`]
+    /// are explicitly *not* round-tripped (see the module docs) so have no
+    /// business appearing in this strategy.
+    fn leaf_type() -> impl Strategy<Value = Type> {
+        prop_oneof![
+            Just(USIZE_T),
+            Just(QB_T),
+            Just(BOOL_T),
+            (0usize..4).prop_map(|idx| Type::new_var_use(idx, TypeBound::Any)),
+        ]
+    }
+
+    fn row(max_len: usize) -> impl Strategy<Value = Vec<Type>> {
+        proptest::collection::vec(leaf_type(), 1..=max_len)
+    }
+
+    fn function_type() -> impl Strategy<Value = FunctionType> {
+        (row(3), row(3)).prop_map(|(input, output)| FunctionType::new(input, output))
+    }
+
+    proptest! {
+        #[test]
+        fn display_then_parse_round_trips(ft in function_type()) {
+            let parsed: FunctionType = ft.to_string().parse().unwrap();
+            prop_assert_eq!(parsed, ft);
+        }
+    }
+
+    #[test]
+    fn custom_type_bound_round_trips() {
+        // Regression test: the bound used to be silently dropped and
+        // hardcoded back to `Any` on parse, so this only failed to round-trip
+        // for a non-`Any` bound - which `leaf_type`'s `USIZE_T` (`Eq`) case
+        // above now also exercises, but spelled out here with every bound
+        // for clarity.
+        for bound in [TypeBound::Eq, TypeBound::Copyable, TypeBound::Any] {
+            let ty = Type::new_extension(CustomType::new(
+                "foo",
+                vec![],
+                ExtensionId::new_unchecked("ext"),
+                bound,
+            ));
+            let ft = FunctionType::new(vec![ty.clone()], vec![ty]);
+            let parsed: FunctionType = ft.to_string().parse().unwrap();
+            assert_eq!(parsed, ft);
+        }
+    }
+}
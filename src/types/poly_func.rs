@@ -2,10 +2,11 @@
 
 use crate::extension::{ExtensionRegistry, SignatureError};
 use itertools::Itertools;
+use smol_str::SmolStr;
 
 use super::{
     type_param::{check_type_args, TypeArg, TypeParam},
-    FunctionType, Substitution,
+    FunctionType, Substitution, SumType, Type, TypeEnum,
 };
 
 /// A polymorphic function type, e.g. of a [Graph], or perhaps an [OpDef].
@@ -76,6 +77,17 @@ impl PolyFuncType {
         self.body.validate(reg, all_var_decls)
     }
 
+    /// Substitutes `sub` into [Self::body], crossing this binder via
+    /// [Substitution::enter_scope] so that indices in `sub` which refer to
+    /// something outside this `PolyFuncType` get shifted up by
+    /// [Self::params]'s length before they're used inside it - the same
+    /// capture-avoiding shift-then-substitute scheme as De Bruijn indices
+    /// anywhere else, just implemented inside [Substitution] itself (which
+    /// lives in the core types module, outside this crate's `types/`
+    /// submodules) rather than as a standalone primitive here. See
+    /// `substitute_under_binder`, `substitute_under_two_nested_binders` and
+    /// `substitute_row_var_under_binder` below for this shifting exercised
+    /// through one, two, and a row-variable binder respectively.
     pub(super) fn substitute(&self, sub: &Substitution) -> Self {
         Self {
             body: self.body.substitute(&sub.enter_scope(self.params.len())),
@@ -117,6 +129,644 @@ impl PolyFuncType {
         check_type_args(args, &self.params)?; // Ensures applicability AND totality
         Ok(self.body.substitute(&Substitution::new(args, ext_reg)))
     }
+
+    /// Infers the [TypeArg]s that would make [Self::body] equal to `target`,
+    /// by walking the two structurally in parallel and, at each occurrence of
+    /// one of [Self::params]' variables in `self.body`, binding it to the
+    /// corresponding subterm of `target`. Every param must be bound by some
+    /// occurrence (unused params can't be inferred this way - use
+    /// [Self::instantiate] and supply them explicitly) and every binding
+    /// found must be consistent with any other occurrence of the same
+    /// variable, or this errors with [SignatureError::InferenceConflict].
+    pub fn infer_instantiate(&self, target: &FunctionType) -> Result<Vec<TypeArg>, SignatureError> {
+        if self.body.input().len() != target.input().len()
+            || self.body.output().len() != target.output().len()
+        {
+            return Err(SignatureError::InvalidTypeArgs);
+        }
+        let mut bindings: Vec<Option<TypeArg>> = vec![None; self.params.len()];
+        for (pat, concrete) in self.body.input().iter().zip(target.input().iter()) {
+            match_type(pat, concrete, &self.params, &mut bindings)?;
+        }
+        for (pat, concrete) in self.body.output().iter().zip(target.output().iter()) {
+            match_type(pat, concrete, &self.params, &mut bindings)?;
+        }
+        bindings
+            .into_iter()
+            .enumerate()
+            .map(|(idx, b)| {
+                b.ok_or(SignatureError::FreeTypeVar {
+                    idx,
+                    num_decls: self.params.len(),
+                })
+            })
+            .collect()
+    }
+
+    /// As [Self::infer_instantiate] followed by [Self::instantiate]: infers
+    /// the [TypeArg]s that make [Self::body] equal to `target`, then
+    /// instantiates `self` with them.
+    pub fn instantiate_inferred(
+        &self,
+        target: &FunctionType,
+        exts: &ExtensionRegistry,
+    ) -> Result<Self, SignatureError> {
+        let args = self.infer_instantiate(target)?;
+        self.instantiate(&args, exts)
+    }
+
+    /// As [Self::infer_instantiate], but [Self::body] may also mention row
+    /// variables: each row is matched positionally the same way, except a
+    /// row variable is anchored by matching the pattern's fixed entries
+    /// before and after it against `target`'s row from the front and back
+    /// respectively, then bound to whatever contiguous span of `target`'s
+    /// row is left over in the middle. Directly returns the resulting
+    /// [Substitution] rather than raw [TypeArg]s, since a row variable's
+    /// binding (a [TypeArg::Sequence]) isn't something a caller could do
+    /// much else with.
+    ///
+    /// A row containing more than one row variable is rejected with
+    /// [SignatureError::AmbiguousRowVariables] even where the variables are
+    /// separated by a fixed entry: disambiguating which of several
+    /// variables a middle span belongs to would need a convention this
+    /// operation doesn't define, so only the (by far the common) single
+    /// row-variable-per-row case is supported.
+    pub fn match_concrete(
+        &self,
+        target: &FunctionType,
+        ext_reg: &ExtensionRegistry,
+    ) -> Result<Substitution, SignatureError> {
+        let mut bindings: Vec<Option<TypeArg>> = vec![None; self.params.len()];
+        match_row(
+            self.body.input(),
+            target.input(),
+            &self.params,
+            &mut bindings,
+        )?;
+        match_row(
+            self.body.output(),
+            target.output(),
+            &self.params,
+            &mut bindings,
+        )?;
+        let args = bindings
+            .into_iter()
+            .enumerate()
+            .map(|(idx, b)| {
+                b.ok_or(SignatureError::FreeTypeVar {
+                    idx,
+                    num_decls: self.params.len(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        check_type_args(&args, &self.params)?;
+        Ok(Substitution::new(&args, ext_reg))
+    }
+}
+
+/// Matches template type `pat` (which may mention `params`' variables)
+/// against concrete type `concrete`, recording a binding in `bindings` for
+/// each variable occurrence and failing with [SignatureError::InvalidTypeArgs]
+/// on a structural (arity or head) mismatch.
+fn match_type(
+    pat: &Type,
+    concrete: &Type,
+    params: &[TypeParam],
+    bindings: &mut [Option<TypeArg>],
+) -> Result<(), SignatureError> {
+    if let TypeEnum::Variable(idx, _) | TypeEnum::RowVariable(idx, _) = pat.as_type_enum() {
+        return bind_var(
+            *idx,
+            TypeArg::Type {
+                ty: concrete.clone(),
+            },
+            bindings,
+        );
+    }
+    match (pat.as_type_enum(), concrete.as_type_enum()) {
+        (TypeEnum::Extension(p), TypeEnum::Extension(c)) => {
+            if p.extension() != c.extension()
+                || p.name() != c.name()
+                || p.args().len() != c.args().len()
+            {
+                return Err(SignatureError::InvalidTypeArgs);
+            }
+            for (pa, ca) in p.args().iter().zip(c.args().iter()) {
+                match_arg(pa, ca, params, bindings)?;
+            }
+            Ok(())
+        }
+        (TypeEnum::Function(pf), TypeEnum::Function(cf)) => {
+            if pf.input().len() != cf.input().len() || pf.output().len() != cf.output().len() {
+                return Err(SignatureError::InvalidTypeArgs);
+            }
+            for (pt, ct) in pf.input().iter().zip(cf.input().iter()) {
+                match_type(pt, ct, params, bindings)?;
+            }
+            for (pt, ct) in pf.output().iter().zip(cf.output().iter()) {
+                match_type(pt, ct, params, bindings)?;
+            }
+            Ok(())
+        }
+        (
+            TypeEnum::Sum(SumType::General { rows: prows }),
+            TypeEnum::Sum(SumType::General { rows: crows }),
+        ) => {
+            if prows.len() != crows.len() {
+                return Err(SignatureError::InvalidTypeArgs);
+            }
+            for (prow, crow) in prows.iter().zip(crows.iter()) {
+                if prow.len() != crow.len() {
+                    return Err(SignatureError::InvalidTypeArgs);
+                }
+                for (pt, ct) in prow.iter().zip(crow.iter()) {
+                    match_type(pt, ct, params, bindings)?;
+                }
+            }
+            Ok(())
+        }
+        _ if pat == concrete => Ok(()),
+        _ => Err(SignatureError::InvalidTypeArgs),
+    }
+}
+
+/// As [match_type], but for an entire row rather than a single [Type]: fixed
+/// entries are matched one-to-one via [match_type], while a
+/// [TypeEnum::RowVariable] is anchored by matching the pattern's fixed
+/// prefix against the front of `concrete` and its fixed suffix against the
+/// back, then binding the variable to whatever middle span of `concrete` is
+/// left over, as a [TypeArg::Sequence].
+fn match_row(
+    pattern: &[Type],
+    concrete: &[Type],
+    params: &[TypeParam],
+    bindings: &mut [Option<TypeArg>],
+) -> Result<(), SignatureError> {
+    let row_mismatch = || SignatureError::RowMatchMismatch {
+        pattern: pattern.iter().cloned().collect(),
+        concrete: concrete.iter().cloned().collect(),
+    };
+    let Some(split) = pattern
+        .iter()
+        .position(|t| matches!(t.as_type_enum(), TypeEnum::RowVariable(..)))
+    else {
+        if pattern.len() != concrete.len() {
+            return Err(row_mismatch());
+        }
+        for (p, c) in pattern.iter().zip(concrete.iter()) {
+            match_type(p, c, params, bindings)?;
+        }
+        return Ok(());
+    };
+    let (prefix, rest) = pattern.split_at(split);
+    let (var, suffix) = rest.split_first().expect("split is within pattern");
+    let TypeEnum::RowVariable(idx, _) = var.as_type_enum() else {
+        unreachable!("split points at a RowVariable by construction")
+    };
+    if let Some(other_idx) = suffix.iter().find_map(|t| match t.as_type_enum() {
+        TypeEnum::RowVariable(other_idx, _) => Some(*other_idx),
+        _ => None,
+    }) {
+        return Err(SignatureError::AmbiguousRowVariables {
+            first_idx: *idx,
+            second_idx: other_idx,
+        });
+    }
+    if concrete.len() < prefix.len() + suffix.len() {
+        return Err(row_mismatch());
+    }
+    let (concrete_prefix, rest) = concrete.split_at(prefix.len());
+    let (concrete_middle, concrete_suffix) = rest.split_at(rest.len() - suffix.len());
+    for (p, c) in prefix.iter().zip(concrete_prefix.iter()) {
+        match_type(p, c, params, bindings)?;
+    }
+    for (p, c) in suffix.iter().zip(concrete_suffix.iter()) {
+        match_type(p, c, params, bindings)?;
+    }
+    bind_var(
+        *idx,
+        TypeArg::Sequence {
+            elems: concrete_middle
+                .iter()
+                .map(|ty| TypeArg::Type { ty: ty.clone() })
+                .collect(),
+        },
+        bindings,
+    )
+}
+
+/// As [match_type], but for a [TypeArg] nested inside a [CustomType](super::CustomType)'s
+/// own args - these can be variable uses of *any* declared [TypeParam] kind
+/// (not just [TypeParam::Type]), so a candidate template is recognised by
+/// comparing it against [TypeArg::new_var_use] for each still-unbound
+/// parameter in turn.
+fn match_arg(
+    pat: &TypeArg,
+    concrete: &TypeArg,
+    params: &[TypeParam],
+    bindings: &mut [Option<TypeArg>],
+) -> Result<(), SignatureError> {
+    if let TypeArg::Type { ty } = pat {
+        if let TypeEnum::Variable(idx, _) | TypeEnum::RowVariable(idx, _) = ty.as_type_enum() {
+            return bind_var(*idx, concrete.clone(), bindings);
+        }
+        return match concrete {
+            TypeArg::Type { ty: cty } => match_type(ty, cty, params, bindings),
+            _ => Err(SignatureError::InvalidTypeArgs),
+        };
+    }
+    for (idx, decl) in params.iter().enumerate() {
+        if bindings[idx].is_none() && *pat == TypeArg::new_var_use(idx, decl.clone()) {
+            return bind_var(idx, concrete.clone(), bindings);
+        }
+    }
+    if pat == concrete {
+        Ok(())
+    } else {
+        Err(SignatureError::InvalidTypeArgs)
+    }
+}
+
+/// Records that the variable at `idx` matches `arg`, failing with
+/// [SignatureError::InferenceConflict] if a previous occurrence of the same
+/// variable was already bound to a different [TypeArg].
+fn bind_var(
+    idx: usize,
+    arg: TypeArg,
+    bindings: &mut [Option<TypeArg>],
+) -> Result<(), SignatureError> {
+    match bindings.get_mut(idx) {
+        None => Err(SignatureError::InvalidTypeArgs),
+        Some(slot @ None) => {
+            *slot = Some(arg);
+            Ok(())
+        }
+        Some(Some(existing)) if *existing == arg => Ok(()),
+        Some(Some(existing)) => Err(SignatureError::InferenceConflict {
+            index: idx,
+            first: existing.clone(),
+            second: arg,
+        }),
+    }
+}
+
+/// The variance of a declared type parameter, inferred from the positions in
+/// which its variable occurs in the body of a [PolyFuncType].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variance {
+    /// The parameter's variable does not occur anywhere in the body, so
+    /// instantiating it with anything fitting its [TypeParam] is sound.
+    Bivariant,
+    /// Occurs only in covariant (output-like) position: a narrower-bounded
+    /// argument than declared is sound.
+    Covariant,
+    /// Occurs only in contravariant (input-like) position: a
+    /// wider-bounded argument than declared is sound.
+    Contravariant,
+    /// Occurs in both covariant and contravariant position (or inside a
+    /// context we can't safely see through, like an opaque extension type's
+    /// own arguments): only the exact declared bound is sound.
+    Invariant,
+}
+
+impl Variance {
+    /// The variance contributed by a single occurrence at `polarity`.
+    fn at(polarity: Polarity) -> Self {
+        match polarity {
+            Polarity::Covariant => Variance::Covariant,
+            Polarity::Contravariant => Variance::Contravariant,
+        }
+    }
+
+    /// Combines the variance seen so far with a newly-found occurrence.
+    /// Occurring at both polarities (covariant + contravariant) collapses to
+    /// [Variance::Invariant], matching ordinary subtyping variance rules.
+    fn combine(self, other: Self) -> Self {
+        use Variance::*;
+        match (self, other) {
+            (Bivariant, v) | (v, Bivariant) => v,
+            (Covariant, Covariant) => Covariant,
+            (Contravariant, Contravariant) => Contravariant,
+            _ => Invariant,
+        }
+    }
+}
+
+/// The polarity of the position currently being traversed: covariant
+/// (output-like, e.g. a [FunctionType]'s outputs) or contravariant
+/// (input-like, e.g. a [FunctionType]'s inputs). Descending into a nested
+/// function type's inputs flips the current polarity.
+#[derive(Clone, Copy)]
+enum Polarity {
+    Covariant,
+    Contravariant,
+}
+
+impl Polarity {
+    fn flip(self) -> Self {
+        match self {
+            Polarity::Covariant => Polarity::Contravariant,
+            Polarity::Contravariant => Polarity::Covariant,
+        }
+    }
+}
+
+impl PolyFuncType {
+    /// Infers the [Variance] of each of [Self::params], by walking
+    /// [Self::body]: parameters start at [Variance::Bivariant] (unused), then
+    /// every occurrence of the parameter's variable in an output-like
+    /// position combines in [Variance::Covariant] and every occurrence in an
+    /// input-like position combines in [Variance::Contravariant]. Descending
+    /// into a nested function type's inputs flips the polarity; descending
+    /// into an opaque [CustomType]'s own arguments is treated as occurring at
+    /// both polarities at once, since we can't see how that extension uses
+    /// them.
+    ///
+    /// [CustomType]: super::CustomType
+    pub fn variances(&self) -> Vec<Variance> {
+        variances_of(self.params.len(), &self.body)
+    }
+
+    /// As [Self::instantiate_all], but uses [Self::variances] to adjust the
+    /// usual bound check: a covariant parameter may be instantiated with a
+    /// narrower-bounded type than declared (as [check_type_args] allows), a
+    /// contravariant parameter also with a wider-bounded one, while unused ([Variance::Bivariant])
+    /// parameters still require the declared bound to contain the argument's,
+    /// exactly as [check_type_args] already enforces. An [Variance::Invariant]
+    /// parameter occurs at both polarities at once, so neither direction of
+    /// subtyping is sound - it requires the argument's bound to match the
+    /// declared one exactly. Only [TypeParam::Type] parameters are affected;
+    /// all others are checked exactly as before.
+    pub fn instantiate_subtyped(
+        &self,
+        args: &[TypeArg],
+        ext_reg: &ExtensionRegistry,
+    ) -> Result<FunctionType, SignatureError> {
+        if args.len() != self.params.len() {
+            return Err(SignatureError::TypeArgMismatch(
+                super::type_param::TypeArgError::WrongNumberArgs(args.len(), self.params.len()),
+            ));
+        }
+        let variances = self.variances();
+        for ((param, arg), variance) in self.params.iter().zip(args.iter()).zip(variances.iter()) {
+            let (TypeParam::Type(decl_bound), TypeArg::Type { ty }) = (param, arg) else {
+                check_type_args(std::slice::from_ref(arg), std::slice::from_ref(param))?;
+                continue;
+            };
+            let arg_bound = ty.least_upper_bound();
+            let sound = match variance {
+                Variance::Covariant | Variance::Bivariant => decl_bound.contains(arg_bound),
+                Variance::Contravariant => arg_bound.contains(*decl_bound),
+                Variance::Invariant => arg_bound == *decl_bound,
+            };
+            if !sound {
+                return Err(SignatureError::TypeVarDoesNotMatchDeclaration {
+                    used: TypeParam::Type(arg_bound),
+                    decl: param.clone(),
+                });
+            }
+        }
+        Ok(self.body.substitute(&Substitution::new(args, ext_reg)))
+    }
+
+    /// Whether `self` is usable wherever `other` is expected, i.e. whether
+    /// `self` is a subtype of `other`: the two must declare the same number
+    /// of parameters, with `other`'s bound for each at least as permissive as
+    /// `self`'s own (the same direction [TypeBound::contains] already checks
+    /// for a single parameter), and [Self::body] must be a subtype of
+    /// `other.body` under the resulting shared binder.
+    pub fn is_subtype_of(&self, other: &Self, reg: &ExtensionRegistry) -> bool {
+        self.params.len() == other.params.len()
+            && self
+                .params
+                .iter()
+                .zip(other.params.iter())
+                .all(
+                    |(self_param, other_param)| match (self_param, other_param) {
+                        (TypeParam::Type(self_bound), TypeParam::Type(other_bound)) => {
+                            other_bound.contains(*self_bound)
+                        }
+                        _ => self_param == other_param,
+                    },
+                )
+            && self.body.is_subtype_of(&other.body, reg)
+    }
+}
+
+impl FunctionType {
+    /// Whether `self` is usable wherever `other` is expected: inputs compare
+    /// contravariantly (`other`'s input must accept whatever `self`'s does)
+    /// and outputs covariantly (`self`'s output must be usable as `other`'s).
+    pub fn is_subtype_of(&self, other: &Self, reg: &ExtensionRegistry) -> bool {
+        self.input().len() == other.input().len()
+            && self.output().len() == other.output().len()
+            && self
+                .input()
+                .iter()
+                .zip(other.input().iter())
+                .all(|(self_in, other_in)| other_in.is_subtype_of(self_in, reg))
+            && self
+                .output()
+                .iter()
+                .zip(other.output().iter())
+                .all(|(self_out, other_out)| self_out.is_subtype_of(other_out, reg))
+    }
+}
+
+impl Type {
+    /// Whether `self` is usable wherever `other` is expected. Variables
+    /// compare by the [TypeBound] lattice (`Eq` <: `Copyable` <: `Any`) in
+    /// the same direction [TypeBound::contains] already checks, nested
+    /// function types recurse via [FunctionType::is_subtype_of], and
+    /// anything else must match exactly.
+    pub fn is_subtype_of(&self, other: &Self, reg: &ExtensionRegistry) -> bool {
+        match (self.as_type_enum(), other.as_type_enum()) {
+            (TypeEnum::Variable(i1, b1), TypeEnum::Variable(i2, b2))
+            | (TypeEnum::RowVariable(i1, b1), TypeEnum::RowVariable(i2, b2)) => {
+                i1 == i2 && b2.contains(*b1)
+            }
+            (TypeEnum::Function(f1), TypeEnum::Function(f2)) => f1.is_subtype_of(f2, reg),
+            _ => self == other,
+        }
+    }
+}
+
+/// Infers the [Variance] of `num_params` declared type parameters from how
+/// their variables occur in `body`, shared between [PolyFuncType::variances]
+/// and [crate::extension::OpDefTypeScheme]'s analogous method.
+pub(crate) fn variances_of(num_params: usize, body: &FunctionType) -> Vec<Variance> {
+    let mut result = vec![Variance::Bivariant; num_params];
+    for t in body.input().iter() {
+        accumulate_variance(t, Polarity::Contravariant, &mut result);
+    }
+    for t in body.output().iter() {
+        accumulate_variance(t, Polarity::Covariant, &mut result);
+    }
+    result
+}
+
+/// Accumulates the variance contributed by each type-variable occurrence in
+/// `ty` at the given `polarity` into `out` (indexed by variable De Bruijn
+/// index), following the same structural recursion as [super::unify::unify_type].
+fn accumulate_variance(ty: &Type, polarity: Polarity, out: &mut [Variance]) {
+    match ty.as_type_enum() {
+        TypeEnum::Variable(idx, _) | TypeEnum::RowVariable(idx, _) => {
+            if let Some(slot) = out.get_mut(*idx) {
+                *slot = slot.combine(Variance::at(polarity));
+            }
+        }
+        TypeEnum::Extension(c) => {
+            // We can't see how the extension's own op uses its args, so
+            // treat them as occurring at both polarities - i.e. invariant.
+            for arg in c.args() {
+                if let TypeArg::Type { ty } = arg {
+                    accumulate_variance(ty, Polarity::Covariant, out);
+                    accumulate_variance(ty, Polarity::Contravariant, out);
+                }
+            }
+        }
+        TypeEnum::Function(ft) => {
+            for t in ft.input().iter() {
+                accumulate_variance(t, polarity.flip(), out);
+            }
+            for t in ft.output().iter() {
+                accumulate_variance(t, polarity, out);
+            }
+        }
+        TypeEnum::Sum(SumType::General { rows }) => {
+            for row in rows {
+                for t in row.iter() {
+                    accumulate_variance(t, polarity, out);
+                }
+            }
+        }
+        TypeEnum::Sum(SumType::Unit { .. }) | TypeEnum::Alias(_) => {}
+    }
+}
+
+impl PolyFuncType {
+    /// Renders `self` like the [Display](std::fmt::Display) impl
+    /// (`forall {params}. {body}`), except that each of [Self::params] gets a
+    /// distinct human-readable name instead of the raw De Bruijn index the
+    /// [Display] impl prints - including at every use site inside a nested
+    /// function type in [Self::body], which shares this same binder rather
+    /// than introducing its own. Turns `forall T0 T1. T0 -> (T1 -> T0)`
+    /// into `forall A B. A -> (B -> A)`.
+    pub fn to_named_string(&self) -> String {
+        let mut names = Vec::new();
+        let mut next = NameSource::default();
+        render_poly_named(self, &mut names, &mut next)
+    }
+}
+
+/// Hands out fresh binder names `A`, `B`, ..., `Z`, then recycles the
+/// alphabet with an extra `'` appended each time round, for
+/// [PolyFuncType::to_named_string].
+#[derive(Default)]
+struct NameSource(usize);
+
+impl NameSource {
+    fn fresh(&mut self) -> SmolStr {
+        let cycle = self.0 / 26;
+        let letter = (b'A' + (self.0 % 26) as u8) as char;
+        self.0 += 1;
+        if cycle == 0 {
+            SmolStr::new(letter.to_string())
+        } else {
+            SmolStr::new(format!("{letter}{}", "'".repeat(cycle)))
+        }
+    }
+}
+
+/// Renders `pf`, assigning a fresh name to each of its params and pushing
+/// them onto `names` (indexed by De Bruijn index, lowest-index-first) before
+/// descending into the body, per the De Bruijn convention used by
+/// [PolyFuncType::substitute] ("type vars declared here go at lowest
+/// indices").
+fn render_poly_named(pf: &PolyFuncType, names: &mut Vec<SmolStr>, next: &mut NameSource) -> String {
+    let fresh: Vec<SmolStr> = pf.params.iter().map(|_| next.fresh()).collect();
+    for name in fresh.iter().rev() {
+        names.insert(0, name.clone());
+    }
+    let body = render_function_named(&pf.body, names, next);
+    names.drain(0..fresh.len());
+    if fresh.is_empty() {
+        body
+    } else {
+        format!("forall {}. {}", fresh.iter().join(" "), body)
+    }
+}
+
+fn render_function_named(ft: &FunctionType, names: &[SmolStr], next: &mut NameSource) -> String {
+    let render_row = |row: Vec<&Type>| -> String {
+        match row.as_slice() {
+            [ty] => render_type_named(ty, names, next),
+            _ => format!(
+                "({})",
+                row.iter()
+                    .map(|ty| render_type_named(ty, names, next))
+                    .join(", ")
+            ),
+        }
+    };
+    format!(
+        "{} -> {}",
+        render_row(ft.input().iter().collect()),
+        render_row(ft.output().iter().collect())
+    )
+}
+
+/// As `render_function_named`, for a single [Type] mentioned in the body. A
+/// nested [TypeEnum::Function] is still a plain [FunctionType] - it shares
+/// the enclosing [PolyFuncType]'s binders rather than introducing its own -
+/// so it's rendered with the same `names` in scope.
+fn render_type_named(ty: &Type, names: &[SmolStr], next: &mut NameSource) -> String {
+    match ty.as_type_enum() {
+        TypeEnum::Variable(idx, _) | TypeEnum::RowVariable(idx, _) => names
+            .get(*idx)
+            .map(SmolStr::to_string)
+            .unwrap_or_else(|| format!("#{idx}")),
+        TypeEnum::Extension(c) => {
+            if c.args().is_empty() {
+                c.name().to_string()
+            } else {
+                let args = c
+                    .args()
+                    .iter()
+                    .map(|a| render_arg_named(a, names, next))
+                    .join(", ");
+                format!("{}({})", c.name(), args)
+            }
+        }
+        TypeEnum::Function(ft) => format!("({})", render_function_named(ft, names, next)),
+        TypeEnum::Sum(SumType::General { rows }) if rows.len() == 1 => format!(
+            "({})",
+            rows[0]
+                .iter()
+                .map(|ty| render_type_named(ty, names, next))
+                .join(", ")
+        ),
+        TypeEnum::Sum(SumType::General { rows }) => format!(
+            "Sum({})",
+            rows.iter()
+                .map(|row| format!(
+                    "({})",
+                    row.iter()
+                        .map(|ty| render_type_named(ty, names, next))
+                        .join(", ")
+                ))
+                .join(" | ")
+        ),
+        TypeEnum::Sum(SumType::Unit { size }) => format!("Sum[{size}]"),
+        TypeEnum::Alias(a) => format!("{a:?}"),
+    }
+}
+
+fn render_arg_named(arg: &TypeArg, names: &[SmolStr], next: &mut NameSource) -> String {
+    match arg {
+        TypeArg::Type { ty } => render_type_named(ty, names, next),
+        other => format!("{other:?}"),
+    }
 }
 
 impl PartialEq<FunctionType> for PolyFuncType {
@@ -131,7 +781,7 @@ pub(crate) mod test {
 
     use smol_str::SmolStr;
 
-    use crate::extension::prelude::{PRELUDE_ID, USIZE_CUSTOM_T, USIZE_T};
+    use crate::extension::prelude::{BOOL_T, PRELUDE_ID, USIZE_CUSTOM_T, USIZE_T};
     use crate::extension::{
         ExtensionId, ExtensionRegistry, SignatureError, TypeDefBound, PRELUDE, PRELUDE_REGISTRY,
     };
@@ -174,6 +824,21 @@ pub(crate) mod test {
         FunctionType::new(vec![t.clone()], vec![t])
     }
 
+    #[test]
+    fn test_to_named_string() -> Result<(), SignatureError> {
+        // forall A. A -> (A -> A) - the nested function type shares the
+        // outer binder rather than introducing its own.
+        let var = Type::new_var_use(0, TypeBound::Any);
+        let inner = Type::new_function(id_fn(var.clone()));
+        let pf = PolyFuncType::new_validated(
+            [TypeParam::Type(TypeBound::Any)],
+            FunctionType::new(vec![var.clone()], vec![inner]),
+            &PRELUDE_REGISTRY,
+        )?;
+        assert_eq!(pf.to_named_string(), "forall A. A -> (A -> A)");
+        Ok(())
+    }
+
     #[test]
     fn test_mismatched_args() -> Result<(), SignatureError> {
         let ar_def = PRELUDE.get_type("array").unwrap();
@@ -343,6 +1008,51 @@ pub(crate) mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_instantiate_subtyped_invariant() -> Result<(), SignatureError> {
+        const EXT_ID: ExtensionId = ExtensionId::new_unchecked("my_ext");
+        const TYPE_NAME: SmolStr = SmolStr::new_inline("MyType");
+        let copyable_ty = Type::new_extension(CustomType::new(
+            TYPE_NAME,
+            vec![],
+            EXT_ID,
+            TypeBound::Copyable,
+        ));
+
+        // forall T: Copyable. T -> T - T occurs in both polarities, so it's
+        // invariant and only an exact-bound argument should be accepted.
+        let var = Type::new_var_use(0, TypeBound::Copyable);
+        let pf = new_pf1(TypeParam::Type(TypeBound::Copyable), var.clone(), var);
+        assert_eq!(pf.variances(), vec![Variance::Invariant]);
+
+        pf.instantiate_subtyped(&[TypeArg::Type { ty: copyable_ty }], &PRELUDE_REGISTRY)?;
+
+        // USIZE_T is narrower (Eq) than the declared Copyable bound - sound
+        // for a covariant parameter, but not for an invariant one.
+        assert_eq!(
+            pf.instantiate_subtyped(&[TypeArg::Type { ty: USIZE_T }], &PRELUDE_REGISTRY)
+                .err(),
+            Some(SignatureError::TypeVarDoesNotMatchDeclaration {
+                used: TypeParam::Type(TypeBound::Eq),
+                decl: TypeParam::Type(TypeBound::Copyable),
+            })
+        );
+
+        // Any is wider than Copyable - unsound in either direction.
+        let any_ty =
+            Type::new_extension(CustomType::new(TYPE_NAME, vec![], EXT_ID, TypeBound::Any));
+        assert_eq!(
+            pf.instantiate_subtyped(&[TypeArg::Type { ty: any_ty }], &PRELUDE_REGISTRY)
+                .err(),
+            Some(SignatureError::TypeVarDoesNotMatchDeclaration {
+                used: TypeParam::Type(TypeBound::Any),
+                decl: TypeParam::Type(TypeBound::Copyable),
+            })
+        );
+
+        Ok(())
+    }
+
     fn new_pf1(param: TypeParam, input: Type, output: Type) -> PolyFuncType {
         PolyFuncType {
             params: vec![param],
@@ -448,6 +1158,126 @@ pub(crate) mod test {
         )
     }
 
+    #[test]
+    fn substitute_under_two_nested_binders() {
+        // forall A. A -> (forall B. B -> (forall C. C -> Tuple(C, B, A)))
+        //
+        // Three levels deep rather than `substitute_under_binder`'s one, so
+        // that a variable declared in the *outermost* scope has to have its
+        // index shifted across two intervening binders (by their combined
+        // parameter count) rather than just one.
+        let reg = [EXTENSION.to_owned(), PRELUDE.to_owned()].into();
+        let innermost = new_pf1(
+            TypeParam::Type(TypeBound::Copyable),
+            Type::new_var_use(0, TypeBound::Copyable),
+            Type::new_tuple(vec![
+                Type::new_var_use(0, TypeBound::Copyable), // C
+                Type::new_var_use(1, TypeBound::Copyable), // B, renumbered
+                Type::new_var_use(2, TypeBound::Any),      // A, renumbered
+            ]),
+        );
+        let middle = new_pf1(
+            TypeParam::Type(TypeBound::Copyable),
+            Type::new_var_use(0, TypeBound::Copyable),
+            Type::new_function(innermost),
+        );
+        let pf = PolyFuncType::new_validated(
+            vec![TypeParam::Type(TypeBound::Any)],
+            FunctionType::new(
+                vec![Type::new_var_use(0, TypeBound::Any)],
+                vec![Type::new_function(middle)],
+            ),
+            &reg,
+        )
+        .unwrap();
+
+        const FREE: usize = 5;
+        const TP_EQ: TypeParam = TypeParam::Type(TypeBound::Eq);
+        let res = pf
+            .instantiate_all(&[TypeArg::new_var_use(FREE, TP_EQ)], &reg)
+            .unwrap();
+
+        let expect_innermost = new_pf1(
+            TypeParam::Type(TypeBound::Copyable),
+            Type::new_var_use(0, TypeBound::Copyable),
+            Type::new_tuple(vec![
+                Type::new_var_use(0, TypeBound::Copyable),
+                Type::new_var_use(1, TypeBound::Copyable),
+                // A was free in the outer scope (now replaced by FREE); by
+                // the time it's used two binders down it must be shifted up
+                // by both of their parameter counts (1 + 1 = 2).
+                Type::new_var_use(FREE + 2, TypeBound::Eq),
+            ]),
+        );
+        let expect_middle = new_pf1(
+            TypeParam::Type(TypeBound::Copyable),
+            Type::new_var_use(0, TypeBound::Copyable),
+            Type::new_function(expect_innermost),
+        );
+        assert_eq!(
+            res,
+            FunctionType::new(
+                vec![Type::new_var_use(FREE, TypeBound::Eq)],
+                vec![Type::new_function(expect_middle)]
+            )
+        );
+    }
+
+    #[test]
+    fn substitute_row_var_under_binder() {
+        // forall R: [Type]. (...R) -> (forall C. C -> Tuple(C, ...R))
+        //
+        // R is a row variable declared in the outer binder. Substituting it
+        // under the inner `forall C` must shift its index by that binder's
+        // one parameter just like an ordinary variable would (see
+        // `substitute_under_binder`); the only row-specific part is that
+        // the substitution splices in as many types as `R` is bound to,
+        // rather than exactly one.
+        let row_param = TypeParam::List(Box::new(TypeParam::Type(TypeBound::Any)));
+        let pf = PolyFuncType::new_validated(
+            vec![row_param],
+            FunctionType::new(
+                vec![Type::new_row_var_use(0, TypeBound::Any)],
+                vec![Type::new_function(new_pf1(
+                    TypeParam::Type(TypeBound::Copyable),
+                    Type::new_var_use(0, TypeBound::Copyable),
+                    Type::new_tuple(vec![
+                        Type::new_var_use(0, TypeBound::Copyable),
+                        Type::new_row_var_use(1, TypeBound::Any), // R, renumbered
+                    ]),
+                ))],
+            ),
+            &PRELUDE_REGISTRY,
+        )
+        .unwrap();
+
+        let row_arg = TypeArg::Sequence {
+            elems: vec![
+                TypeArg::Type { ty: USIZE_T },
+                TypeArg::Type { ty: BOOL_T },
+            ],
+        };
+        let res = pf
+            .instantiate_all(std::slice::from_ref(&row_arg), &PRELUDE_REGISTRY)
+            .unwrap();
+
+        assert_eq!(
+            res,
+            FunctionType::new(
+                vec![USIZE_T, BOOL_T],
+                vec![Type::new_function(new_pf1(
+                    TypeParam::Type(TypeBound::Copyable),
+                    Type::new_var_use(0, TypeBound::Copyable),
+                    Type::new_tuple(vec![
+                        Type::new_var_use(0, TypeBound::Copyable),
+                        USIZE_T,
+                        BOOL_T,
+                    ]),
+                ))]
+            )
+        );
+    }
+
     const USIZE_TA: TypeArg = TypeArg::Type { ty: USIZE_T };
 
     #[test]
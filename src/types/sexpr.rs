@@ -0,0 +1,511 @@
+//! [`hugr_sexpr::Export`] support for the type system, giving `Type` and its
+//! constituents a human-readable, diffable textual form that complements the
+//! serde/JSON encoding (see [`super::cbor`] for the binary counterpart).
+//!
+//! The derive only understands plain structs built out of fields that are
+//! themselves `Export` (strings, and other `Export` types, combined with
+//! `#[sexpr(required)]`/`#[sexpr(optional)]`/`#[sexpr(repeated)]`), so each
+//! type here is exported via a small shadow struct with that shape rather
+//! than by deriving `Export` directly on the real type - that keeps the
+//! mapping explicit and lets us pick readable field names (`custom`, `sum`,
+//! `variant`, ...) independent of the Rust field names.
+//!
+//! Every variant is tagged with a leading string atom (`"var"`, `"custom"`,
+//! `"sum"`, ...) and every nested [`Type`]/[`TypeArg`] is exported through
+//! its own `Export` impl rather than being flattened through `Display` into
+//! one opaque string - that's what lets [`read_type_sexpr`] reconstruct the
+//! real value back out, instead of only returning the raw parsed
+//! [`Value`]s.
+use hugr_sexpr::{export::Export, read_values, Value};
+use thiserror::Error;
+
+use super::type_param::TypeArg;
+use super::{CustomType, FunctionType, SumType, Type, TypeBound, TypeEnum};
+use crate::extension::{ExtensionId, ExtensionSet};
+
+impl TypeBound {
+    /// The single-letter symbol this bound exports as: `E` for
+    /// [`TypeBound::Eq`], `C` for [`TypeBound::Copyable`], `A` for
+    /// [`TypeBound::Any`].
+    fn sexpr_symbol(&self) -> &'static str {
+        match self {
+            TypeBound::Eq => "E",
+            TypeBound::Copyable => "C",
+            TypeBound::Any => "A",
+        }
+    }
+
+    fn from_sexpr_symbol(s: &str) -> Option<Self> {
+        match s {
+            "E" => Some(TypeBound::Eq),
+            "C" => Some(TypeBound::Copyable),
+            "A" => Some(TypeBound::Any),
+            _ => None,
+        }
+    }
+}
+
+impl Export for TypeBound {
+    fn export(&self) -> Vec<Value> {
+        self.sexpr_symbol().to_string().export()
+    }
+}
+
+#[derive(Export)]
+struct CustomTypeSexpr {
+    tag: String,
+    extension: String,
+    name: String,
+    #[sexpr(repeated)]
+    args: Vec<TypeArg>,
+    bound: String,
+}
+
+impl Export for CustomType {
+    /// Exports as the flat, tagged sequence `"custom" <extension> <name>
+    /// (args ...)* <bound>`, with each [`TypeArg`] exported recursively
+    /// through its own [`Export`] impl rather than stringified.
+    fn export(&self) -> Vec<Value> {
+        CustomTypeSexpr {
+            tag: "custom".to_string(),
+            extension: self.extension().to_string(),
+            name: self.name().to_string(),
+            args: self.args().to_vec(),
+            bound: self.bound().sexpr_symbol().to_string(),
+        }
+        .export()
+    }
+}
+
+#[derive(Export)]
+struct TypeArgTypeSexpr {
+    tag: String,
+    #[sexpr(required)]
+    ty: Type,
+}
+
+#[derive(Export)]
+struct TypeArgNatSexpr {
+    tag: String,
+    value: String,
+}
+
+#[derive(Export)]
+struct TypeArgStringSexpr {
+    tag: String,
+    value: String,
+}
+
+#[derive(Export)]
+struct TypeArgSeqSexpr {
+    tag: String,
+    #[sexpr(repeated)]
+    elems: Vec<TypeArg>,
+}
+
+impl Export for TypeArg {
+    /// Exports each variant as a leading tag atom followed by its real
+    /// payload - a nested [`Type`] recurses through [`Type::export`] rather
+    /// than being rendered via `Display`.
+    fn export(&self) -> Vec<Value> {
+        match self {
+            TypeArg::Type { ty } => TypeArgTypeSexpr {
+                tag: "type".to_string(),
+                ty: ty.clone(),
+            }
+            .export(),
+            TypeArg::BoundedNat { n } => TypeArgNatSexpr {
+                tag: "nat".to_string(),
+                value: n.to_string(),
+            }
+            .export(),
+            TypeArg::String { value } => TypeArgStringSexpr {
+                tag: "str".to_string(),
+                value: value.clone(),
+            }
+            .export(),
+            TypeArg::Sequence { elems } => TypeArgSeqSexpr {
+                tag: "seq".to_string(),
+                elems: elems.clone(),
+            }
+            .export(),
+            other => format!("<unsupported arg {other:?}>").export(),
+        }
+    }
+}
+
+#[derive(Export)]
+struct SumVariant {
+    #[sexpr(repeated)]
+    types: Vec<Type>,
+}
+
+#[derive(Export)]
+struct SumUnitSexpr {
+    tag: String,
+    size: String,
+}
+
+#[derive(Export)]
+struct SumGeneralSexpr {
+    tag: String,
+    #[sexpr(repeated)]
+    variants: Vec<SumVariant>,
+}
+
+impl Export for SumType {
+    /// Exports `SumType::Unit` as `"sum-unit" <size>` and `SumType::General`
+    /// as `"sum" (variants (types ...))*`, with each variant row's
+    /// [`Type`]s exported recursively instead of joined into one string.
+    fn export(&self) -> Vec<Value> {
+        match self {
+            SumType::Unit { size } => SumUnitSexpr {
+                tag: "sum-unit".to_string(),
+                size: size.to_string(),
+            }
+            .export(),
+            SumType::General { rows } => SumGeneralSexpr {
+                tag: "sum".to_string(),
+                variants: rows
+                    .iter()
+                    .map(|row| SumVariant { types: row.clone() })
+                    .collect(),
+            }
+            .export(),
+        }
+    }
+}
+
+#[derive(Export)]
+struct VarSexpr {
+    tag: String,
+    idx: String,
+    bound: String,
+}
+
+#[derive(Export)]
+struct FunctionSexpr {
+    tag: String,
+    #[sexpr(repeated)]
+    input: Vec<Type>,
+    #[sexpr(repeated)]
+    ext_reqs: Vec<String>,
+    #[sexpr(repeated)]
+    output: Vec<Type>,
+}
+
+impl Export for Type {
+    /// Exports each [`TypeEnum`] variant through its own tagged shadow shape
+    /// - see the structs above - instead of going through `Display`, so that
+    /// [`read_type_sexpr`] can reconstruct the real value afterwards.
+    fn export(&self) -> Vec<Value> {
+        match self.as_type_enum() {
+            TypeEnum::Variable(idx, bound) => VarSexpr {
+                tag: "var".to_string(),
+                idx: idx.to_string(),
+                bound: bound.sexpr_symbol().to_string(),
+            }
+            .export(),
+            TypeEnum::RowVariable(idx, bound) => VarSexpr {
+                tag: "row-var".to_string(),
+                idx: idx.to_string(),
+                bound: bound.sexpr_symbol().to_string(),
+            }
+            .export(),
+            TypeEnum::Extension(custy) => custy.export(),
+            TypeEnum::Function(ft) => FunctionSexpr {
+                tag: "function".to_string(),
+                input: ft.input().to_vec(),
+                ext_reqs: ft.extension_reqs.iter().map(|e| e.to_string()).collect(),
+                output: ft.output().to_vec(),
+            }
+            .export(),
+            TypeEnum::Sum(s) => s.export(),
+            // Unlike every other variant, an `AliasDecl`'s fields aren't
+            // visible outside `super` (see `parse.rs`'s `DisplayType`,
+            // which has the same limitation), so there's no real structured
+            // shape to export here - only an honest, unparseable marker.
+            TypeEnum::Alias(a) => format!("<unsupported alias {a:?}>").export(),
+        }
+    }
+}
+
+/// An error reconstructing a [`Type`] from the textual form produced by the
+/// [`Export`] impls above.
+#[derive(Debug, Error)]
+pub enum ReadTypeError {
+    /// The input wasn't well-formed s-expression syntax at all.
+    #[error("malformed sexpr input: {0}")]
+    Read(#[from] hugr_sexpr::ReadError),
+    /// The input ended before a complete value was read.
+    #[error("unexpected end of input, expected {0}")]
+    UnexpectedEof(&'static str),
+    /// A value wasn't shaped the way the corresponding `Export` impl above
+    /// produces it (e.g. a tag where a nested list was expected, or vice
+    /// versa).
+    #[error("malformed sexpr structure: {0}")]
+    Malformed(String),
+    /// The tag was recognised as belonging to a [`Type`]/[`TypeArg`] variant
+    /// that has no real structured export (currently just
+    /// [`TypeEnum::Alias`]), or wasn't recognised as a tag at all.
+    #[error("{0} is not parseable back into a Type")]
+    Unsupported(String),
+}
+
+/// A cursor over a not-yet-consumed slice of parsed [`Value`]s.
+struct SexprCursor<'a> {
+    rest: &'a [Value],
+}
+
+impl<'a> SexprCursor<'a> {
+    fn new(values: &'a [Value]) -> Self {
+        Self { rest: values }
+    }
+
+    fn eat_atom(&mut self, expected: &'static str) -> Result<&'a str, ReadTypeError> {
+        let (first, rest) = self
+            .rest
+            .split_first()
+            .ok_or(ReadTypeError::UnexpectedEof(expected))?;
+        let Value::Atom(s) = first else {
+            return Err(ReadTypeError::Malformed(format!(
+                "expected {expected}, found a nested list"
+            )));
+        };
+        self.rest = rest;
+        Ok(s.as_str())
+    }
+
+    fn eat_nat(&mut self, expected: &'static str) -> Result<u64, ReadTypeError> {
+        let s = self.eat_atom(expected)?;
+        s.parse()
+            .map_err(|_| ReadTypeError::Malformed(format!("expected a number, found '{s}'")))
+    }
+
+    fn eat_bound(&mut self) -> Result<TypeBound, ReadTypeError> {
+        let s = self.eat_atom("a type bound")?;
+        TypeBound::from_sexpr_symbol(s)
+            .ok_or_else(|| ReadTypeError::Malformed(format!("'{s}' is not a valid type bound")))
+    }
+
+    /// Consumes the next `(name ...)` sublist if present, returning a cursor
+    /// over its contents. Leaves `self` untouched and returns `None` if the
+    /// next value isn't a `name`-tagged list.
+    fn eat_named(&mut self, name: &str) -> Option<SexprCursor<'a>> {
+        let (first, rest) = self.rest.split_first()?;
+        let Value::List(items) = first else {
+            return None;
+        };
+        let (head, body) = items.split_first()?;
+        let Value::Atom(head) = head else {
+            return None;
+        };
+        if head != name {
+            return None;
+        }
+        self.rest = rest;
+        Some(SexprCursor { rest: body })
+    }
+
+    /// Repeatedly consumes `(name ...)` sublists for as long as they appear,
+    /// mirroring how `#[sexpr(repeated)]` wraps each item of a `Vec` field.
+    fn eat_all_named(&mut self, name: &str) -> Vec<SexprCursor<'a>> {
+        let mut out = Vec::new();
+        while let Some(inner) = self.eat_named(name) {
+            out.push(inner);
+        }
+        out
+    }
+
+    fn finish(self) -> Result<(), ReadTypeError> {
+        if self.rest.is_empty() {
+            Ok(())
+        } else {
+            Err(ReadTypeError::Malformed(format!(
+                "unexpected trailing data: {:?}",
+                self.rest
+            )))
+        }
+    }
+
+    fn eat_type(&mut self) -> Result<Type, ReadTypeError> {
+        let tag = self.eat_atom("a type tag")?.to_string();
+        match tag.as_str() {
+            "var" => {
+                let idx = self.eat_nat("a type variable index")?;
+                let bound = self.eat_bound()?;
+                Ok(Type::new_var_use(idx as usize, bound))
+            }
+            "row-var" => {
+                let idx = self.eat_nat("a row variable index")?;
+                let bound = self.eat_bound()?;
+                Ok(Type::new_row_var_use(idx as usize, bound))
+            }
+            "custom" => {
+                let extension = self.eat_atom("an extension id")?.to_string();
+                let name = self.eat_atom("a type name")?.to_string();
+                let args = self
+                    .eat_all_named("args")
+                    .into_iter()
+                    .map(|mut c| c.eat_type_arg())
+                    .collect::<Result<Vec<_>, _>>()?;
+                let bound = self.eat_bound()?;
+                Ok(Type::new_extension(CustomType::new(
+                    name,
+                    args,
+                    ExtensionId::new_unchecked(extension),
+                    bound,
+                )))
+            }
+            "sum-unit" => {
+                let size = self.eat_nat("a sum size")?;
+                Ok(Type::new_unit_sum(size))
+            }
+            "sum" => {
+                let rows = self
+                    .eat_all_named("variants")
+                    .into_iter()
+                    .map(|mut variant| {
+                        let row = variant
+                            .eat_all_named("types")
+                            .into_iter()
+                            .map(|mut c| c.eat_type())
+                            .collect::<Result<Vec<_>, _>>()?;
+                        variant.finish()?;
+                        Ok(row)
+                    })
+                    .collect::<Result<Vec<_>, ReadTypeError>>()?;
+                Ok(Type::from(SumType::General { rows }))
+            }
+            "function" => {
+                let input = self
+                    .eat_all_named("input")
+                    .into_iter()
+                    .map(|mut c| c.eat_type())
+                    .collect::<Result<Vec<_>, _>>()?;
+                let ext_reqs: ExtensionSet = self
+                    .eat_all_named("ext_reqs")
+                    .into_iter()
+                    .map(|mut c| {
+                        c.eat_atom("an extension name")
+                            .map(ExtensionId::new_unchecked)
+                    })
+                    .collect::<Result<_, _>>()?;
+                let output = self
+                    .eat_all_named("output")
+                    .into_iter()
+                    .map(|mut c| c.eat_type())
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Type::new_function(
+                    FunctionType::new(input, output).with_extension_delta(&ext_reqs),
+                ))
+            }
+            other => Err(ReadTypeError::Unsupported(other.to_string())),
+        }
+    }
+
+    fn eat_type_arg(&mut self) -> Result<TypeArg, ReadTypeError> {
+        let tag = self.eat_atom("a type-arg tag")?.to_string();
+        match tag.as_str() {
+            "type" => {
+                let mut inner = self
+                    .eat_named("ty")
+                    .ok_or_else(|| ReadTypeError::Malformed("expected a 'ty' field".to_string()))?;
+                let ty = inner.eat_type()?;
+                inner.finish()?;
+                Ok(TypeArg::Type { ty })
+            }
+            "nat" => {
+                let n = self.eat_nat("a bounded-nat value")?;
+                Ok(TypeArg::BoundedNat { n })
+            }
+            "str" => {
+                let value = self.eat_atom("a string value")?.to_string();
+                Ok(TypeArg::String { value })
+            }
+            "seq" => {
+                let elems = self
+                    .eat_all_named("elems")
+                    .into_iter()
+                    .map(|mut c| c.eat_type_arg())
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(TypeArg::Sequence { elems })
+            }
+            other => Err(ReadTypeError::Unsupported(other.to_string())),
+        }
+    }
+}
+
+/// Reconstructs the [`Type`] that [`Type::export`] produced, by walking the
+/// tagged [`Value`] tree [`read_values`] parses `input` into. Every variant
+/// round-trips except [`TypeEnum::Alias`] (and [`TypeArg`]s other than
+/// types, nats, strings and sequences), which [`ReadTypeError::
+/// Unsupported`] is returned for rather than silently producing the wrong
+/// type.
+pub fn read_type_sexpr(input: &str) -> Result<Type, ReadTypeError> {
+    let values = read_values(input)?;
+    let mut cursor = SexprCursor::new(&values);
+    let ty = cursor.eat_type()?;
+    cursor.finish()?;
+    Ok(ty)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn exports_and_reads_back_a_type_variable() {
+        let ty = Type::new_var_use(2, TypeBound::Copyable);
+        let text = r#""var" "2" "C""#;
+        assert_eq!(ty.export(), read_values(text).unwrap());
+        assert_eq!(read_type_sexpr(text).unwrap(), ty);
+    }
+
+    #[test]
+    fn exports_and_reads_back_a_custom_type_with_args() {
+        let ext = ExtensionId::new_unchecked("my_ext");
+        let ty = Type::new_extension(CustomType::new(
+            "MyType",
+            vec![TypeArg::BoundedNat { n: 3 }],
+            ext,
+            TypeBound::Copyable,
+        ));
+        let text = r#""custom" "my_ext" "MyType" (args "nat" "3") "C""#;
+        assert_eq!(ty.export(), read_values(text).unwrap());
+        assert_eq!(read_type_sexpr(text).unwrap(), ty);
+    }
+
+    #[test]
+    fn exports_and_reads_back_a_function_type() {
+        let var = Type::new_var_use(0, TypeBound::Any);
+        let unit = Type::new_unit_sum(1);
+        let ty = Type::new_function(FunctionType::new(vec![var.clone()], vec![unit.clone()]));
+        let text = r#""function" (input "var" "0" "A") (output "sum-unit" "1")"#;
+        assert_eq!(ty.export(), read_values(text).unwrap());
+        assert_eq!(read_type_sexpr(text).unwrap(), ty);
+    }
+
+    #[test]
+    fn round_trips_a_general_sum_without_joining_rows_into_one_string() {
+        let var = Type::new_var_use(0, TypeBound::Any);
+        let unit = Type::new_unit_sum(2);
+        let ty = Type::from(SumType::General {
+            rows: vec![vec![var.clone()], vec![unit.clone()]],
+        });
+        let text = concat!(
+            r#""sum" (variants (types "var" "0" "A")) "#,
+            r#"(variants (types "sum-unit" "2"))"#
+        );
+        assert_eq!(ty.export(), read_values(text).unwrap());
+        assert_eq!(read_type_sexpr(text).unwrap(), ty);
+    }
+
+    #[test]
+    fn rejects_an_unrecognized_tag_instead_of_guessing() {
+        assert!(matches!(
+            read_type_sexpr(r#""bogus" "0""#),
+            Err(ReadTypeError::Unsupported(_))
+        ));
+    }
+}
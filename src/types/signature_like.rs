@@ -0,0 +1,55 @@
+//! A trait for types shaped like a [`FunctionType`]: an input row, an output
+//! row and a set of extension requirements.
+//!
+//! This tree doesn't split "signature that may contain row variables" and
+//! "signature known to be concrete" into two separate monomorphizations the
+//! way a `FunctionType<const ROWVARS: bool>` generic would - a single
+//! [`FunctionType`] carries both cases via [`TypeEnum::RowVariable`], checked
+//! dynamically rather than tracked in the type system. [`SignatureLike`] is
+//! still worth having even with one implementor: it gives validation, type
+//! inference and rewriting passes a name to write `impl SignatureLike`
+//! against instead of `FunctionType` directly, so a second implementor (a
+//! `PolyFuncType`-free "signature view" over a [`crate::ops::OpType`], say)
+//! can be added later without disturbing any of those call sites.
+use crate::extension::ExtensionSet;
+
+use super::{FunctionType, Substitution, TypeRow};
+
+/// Common shape shared by anything that behaves like a [`FunctionType`].
+pub trait SignatureLike {
+    /// The row type used for [`Self::input`]/[`Self::output`].
+    type Row;
+
+    /// The input row.
+    fn input(&self) -> &Self::Row;
+
+    /// The output row.
+    fn output(&self) -> &Self::Row;
+
+    /// The extensions required to execute something of this signature.
+    fn extension_reqs(&self) -> &ExtensionSet;
+
+    /// Applies `sub`, replacing every type variable (and splicing in every
+    /// row variable) the signature mentions.
+    fn substitute(&self, sub: &Substitution) -> Self;
+}
+
+impl SignatureLike for FunctionType {
+    type Row = TypeRow;
+
+    fn input(&self) -> &TypeRow {
+        FunctionType::input(self)
+    }
+
+    fn output(&self) -> &TypeRow {
+        FunctionType::output(self)
+    }
+
+    fn extension_reqs(&self) -> &ExtensionSet {
+        &self.extension_reqs
+    }
+
+    fn substitute(&self, sub: &Substitution) -> Self {
+        FunctionType::substitute(self, sub)
+    }
+}
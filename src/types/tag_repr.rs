@@ -0,0 +1,81 @@
+//! Tag representation selection for [`SumType`].
+//!
+//! Mirrors how rustc derives a concrete discriminant integer type from an
+//! ADT's repr (`adt_def.repr.discr_type()`): given a sum type's variant
+//! count, [`TagRepr::smallest_for`] picks the smallest unsigned integer width
+//! that can index every variant, so that lowering/codegen passes have a
+//! predictable default in-memory tag width instead of having to guess one.
+use super::SumType;
+
+/// The integer type used to discriminate a sum type's variants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TagRepr {
+    /// An 8-bit tag, big enough for up to 256 variants.
+    U8,
+    /// A 16-bit tag, big enough for up to 65536 variants.
+    U16,
+    /// A 32-bit tag.
+    U32,
+    /// A 64-bit tag.
+    U64,
+}
+
+impl TagRepr {
+    /// The width of this representation, in bits.
+    pub fn bits(&self) -> u32 {
+        match self {
+            TagRepr::U8 => 8,
+            TagRepr::U16 => 16,
+            TagRepr::U32 => 32,
+            TagRepr::U64 => 64,
+        }
+    }
+
+    /// The number of distinct variants this representation can index.
+    ///
+    /// Saturates at `u64::MAX` for [`TagRepr::U64`] rather than overflowing,
+    /// since `2^64` doesn't fit in a `u64`.
+    pub fn max_variants(&self) -> u64 {
+        match self {
+            TagRepr::U64 => u64::MAX,
+            _ => 1u64 << self.bits(),
+        }
+    }
+
+    /// The smallest [`TagRepr`] able to index `num_variants` distinct
+    /// variants.
+    ///
+    /// Saturates at [`TagRepr::U64`] if `num_variants` exceeds what even a
+    /// 64-bit tag can index, rather than panicking - such a sum type can't
+    /// be lowered to a concrete representation at all, but that's a concern
+    /// for the lowering pass, not for picking a width here.
+    pub fn smallest_for(num_variants: u64) -> TagRepr {
+        if num_variants <= TagRepr::U8.max_variants() {
+            TagRepr::U8
+        } else if num_variants <= TagRepr::U16.max_variants() {
+            TagRepr::U16
+        } else if num_variants <= TagRepr::U32.max_variants() {
+            TagRepr::U32
+        } else {
+            TagRepr::U64
+        }
+    }
+}
+
+impl SumType {
+    /// The smallest [`TagRepr`] able to index this sum type's variants.
+    ///
+    /// This is always the *computed* representation, derived purely from the
+    /// variant count. `SumType` in this tree has no field to carry an
+    /// explicit, user-chosen override (that would require a `tag: TagRepr`
+    /// field or a `SumType::WithRepr` variant on the enum itself, which is
+    /// defined outside this module), so unlike the rustc-style repr this
+    /// mirrors, there's currently no way to pin a wider tag than necessary.
+    pub fn tag_repr(&self) -> TagRepr {
+        let num_variants = match self {
+            SumType::Unit { size } => *size as u64,
+            SumType::General { rows } => rows.len() as u64,
+        };
+        TagRepr::smallest_for(num_variants)
+    }
+}
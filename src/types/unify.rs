@@ -0,0 +1,216 @@
+//! Unification of a polymorphic pattern [`Type`] against a concrete target,
+//! to automatically infer the [`TypeArg`]s needed to call
+//! [`PolyFuncType::instantiate`](super::PolyFuncType::instantiate).
+//!
+//! This is the inference counterpart to the existing
+//! [`Substitution`]/[`Type::substitute`] machinery: rather than being given
+//! the [`TypeArg`]s up front, we work them out by walking a pattern type
+//! (which may mention [`TypeEnum::Variable`]s bound by a [`PolyFuncType`])
+//! and a concrete type in lockstep.
+use itertools::Itertools;
+
+use super::{
+    type_param::{TypeArg, TypeParam},
+    CustomType, FunctionType, Type, TypeEnum,
+};
+
+/// An error produced when a pattern [`Type`] (containing variables) cannot be
+/// unified with a concrete target [`Type`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum UnificationError {
+    #[error("Variable {idx} occurs within its own binding {var_bound:?} -> {ty}")]
+    OccursCheckFailed {
+        idx: usize,
+        var_bound: TypeParam,
+        ty: Type,
+    },
+    #[error("Type {ty} does not fit the bound {bound:?} required for variable {idx}")]
+    BoundMismatch {
+        idx: usize,
+        bound: TypeParam,
+        ty: Type,
+    },
+    #[error("Variable {idx} was already bound to {prev}, cannot also bind to {new}")]
+    ConflictingBinding { idx: usize, prev: Type, new: Type },
+    #[error("Could not unify {pattern} with {target} - different shape")]
+    Mismatch { pattern: Type, target: Type },
+}
+
+/// Attempts to unify a polymorphic `pattern` (which may contain
+/// [`TypeEnum::Variable`]s, as used in the body of a [`PolyFuncType`])
+/// against a concrete `target` type with no free variables, producing
+/// bindings for each variable index encountered.
+///
+/// On success, returns a `Vec` the same length as the highest variable index
+/// seen plus one, with `Some(arg)` for each variable that was bound and
+/// `None` for indices that did not occur in `pattern`. Once every entry is
+/// `Some`, the unwrapped arguments can be passed to
+/// [`PolyFuncType::instantiate`](super::PolyFuncType::instantiate); a `None`
+/// entry has to be supplied by the caller first.
+pub fn unify_type(pattern: &Type, target: &Type) -> Result<Vec<Option<TypeArg>>, UnificationError> {
+    let mut bindings = Vec::new();
+    unify_into(pattern, target, &mut bindings)?;
+    Ok(bindings)
+}
+
+fn unify_into(
+    pattern: &Type,
+    target: &Type,
+    bindings: &mut Vec<Option<TypeArg>>,
+) -> Result<(), UnificationError> {
+    if let TypeEnum::Variable(idx, bound) = pattern.as_type_enum() {
+        if occurs_in(*idx, target) {
+            return Err(UnificationError::OccursCheckFailed {
+                idx: *idx,
+                var_bound: TypeParam::Type(*bound),
+                ty: target.clone(),
+            });
+        }
+        if !bound.contains(target.least_upper_bound()) {
+            return Err(UnificationError::BoundMismatch {
+                idx: *idx,
+                bound: TypeParam::Type(*bound),
+                ty: target.clone(),
+            });
+        }
+        if bindings.len() <= *idx {
+            bindings.resize(*idx + 1, None);
+        }
+        match &bindings[*idx] {
+            None => bindings[*idx] = Some(TypeArg::Type { ty: target.clone() }),
+            Some(TypeArg::Type { ty }) if ty == target => {}
+            Some(TypeArg::Type { ty }) => {
+                return Err(UnificationError::ConflictingBinding {
+                    idx: *idx,
+                    prev: ty.clone(),
+                    new: target.clone(),
+                })
+            }
+            Some(_) => unreachable!("Variable of Type kind bound to non-Type arg"),
+        }
+        return Ok(());
+    }
+
+    match (pattern.as_type_enum(), target.as_type_enum()) {
+        (TypeEnum::Sum(p), TypeEnum::Sum(t)) => unify_sum(p, t, bindings),
+        (TypeEnum::Function(p), TypeEnum::Function(t)) => unify_function(p, t, bindings),
+        (TypeEnum::Extension(p), TypeEnum::Extension(t)) => unify_custom(p, t, bindings),
+        _ => Err(UnificationError::Mismatch {
+            pattern: pattern.clone(),
+            target: target.clone(),
+        }),
+    }
+}
+
+fn unify_sum(
+    pattern: &super::SumType,
+    target: &super::SumType,
+    bindings: &mut Vec<Option<TypeArg>>,
+) -> Result<(), UnificationError> {
+    let (Some(p_rows), Some(t_rows)) = (sum_rows(pattern), sum_rows(target)) else {
+        return if pattern == target {
+            Ok(())
+        } else {
+            Err(UnificationError::Mismatch {
+                pattern: pattern.clone().into(),
+                target: target.clone().into(),
+            })
+        };
+    };
+    if p_rows.len() != t_rows.len() {
+        return Err(UnificationError::Mismatch {
+            pattern: pattern.clone().into(),
+            target: target.clone().into(),
+        });
+    }
+    for (p_row, t_row) in p_rows.iter().zip(t_rows.iter()) {
+        if p_row.len() != t_row.len() {
+            return Err(UnificationError::Mismatch {
+                pattern: pattern.clone().into(),
+                target: target.clone().into(),
+            });
+        }
+        for (p, t) in p_row.iter().zip(t_row.iter()) {
+            unify_into(p, t, bindings)?;
+        }
+    }
+    Ok(())
+}
+
+fn sum_rows(s: &super::SumType) -> Option<Vec<&super::TypeRow>> {
+    match s {
+        super::SumType::General { rows } => Some(rows.iter().collect_vec()),
+        super::SumType::Unit { .. } => None,
+    }
+}
+
+fn unify_function(
+    pattern: &FunctionType,
+    target: &FunctionType,
+    bindings: &mut Vec<Option<TypeArg>>,
+) -> Result<(), UnificationError> {
+    if pattern.input().len() != target.input().len() || pattern.output().len() != target.output().len() {
+        return Err(UnificationError::Mismatch {
+            pattern: Type::new_function(pattern.clone()),
+            target: Type::new_function(target.clone()),
+        });
+    }
+    for (p, t) in pattern.input().iter().zip(target.input().iter()) {
+        unify_into(p, t, bindings)?;
+    }
+    for (p, t) in pattern.output().iter().zip(target.output().iter()) {
+        unify_into(p, t, bindings)?;
+    }
+    Ok(())
+}
+
+fn unify_custom(
+    pattern: &CustomType,
+    target: &CustomType,
+    bindings: &mut Vec<Option<TypeArg>>,
+) -> Result<(), UnificationError> {
+    if pattern.extension() != target.extension() || pattern.name() != target.name() {
+        return Err(UnificationError::Mismatch {
+            pattern: Type::new_extension(pattern.clone()),
+            target: Type::new_extension(target.clone()),
+        });
+    }
+    if pattern.args().len() != target.args().len() {
+        return Err(UnificationError::Mismatch {
+            pattern: Type::new_extension(pattern.clone()),
+            target: Type::new_extension(target.clone()),
+        });
+    }
+    for (p, t) in pattern.args().iter().zip(target.args().iter()) {
+        match (p, t) {
+            (TypeArg::Type { ty: p }, TypeArg::Type { ty: t }) => unify_into(p, t, bindings)?,
+            (p, t) if p == t => {}
+            _ => {
+                return Err(UnificationError::Mismatch {
+                    pattern: Type::new_extension(pattern.clone()),
+                    target: Type::new_extension(target.clone()),
+                })
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether the type variable at `idx` occurs anywhere within `ty`.
+fn occurs_in(idx: usize, ty: &Type) -> bool {
+    match ty.as_type_enum() {
+        TypeEnum::Variable(i, _) | TypeEnum::RowVariable(i, _) => *i == idx,
+        TypeEnum::Extension(c) => c
+            .args()
+            .iter()
+            .any(|a| matches!(a, TypeArg::Type { ty } if occurs_in(idx, ty))),
+        TypeEnum::Function(ft) => {
+            ft.input().iter().any(|t| occurs_in(idx, t)) || ft.output().iter().any(|t| occurs_in(idx, t))
+        }
+        TypeEnum::Sum(super::SumType::General { rows }) => rows
+            .iter()
+            .any(|row| row.iter().any(|t| occurs_in(idx, t))),
+        TypeEnum::Sum(super::SumType::Unit { .. }) | TypeEnum::Alias(_) => false,
+    }
+}